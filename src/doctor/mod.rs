@@ -0,0 +1,413 @@
+use crate::install::{detect_best_install, Arma3Install};
+use crate::mods::{validate_local_mod_dir, LocalMod};
+#[cfg(target_os = "linux")]
+use crate::steam::snap;
+use crate::steam::{detect_steam_installations, SteamInstallation, SteamPackage};
+use std::path::Path;
+
+/// Outcome of a single [`DoctorCheck`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckStatus {
+    /// Everything is in order.
+    Pass,
+    /// Launching may work, but something is off.
+    Warn,
+    /// Launching will fail.
+    Fail,
+}
+
+/// One item of a [`DoctorReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoctorCheck {
+    name: &'static str,
+    status: CheckStatus,
+    detail: String,
+    hint: Option<String>,
+}
+
+impl DoctorCheck {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    /// What was checked (e.g. `steam root`).
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Check outcome.
+    pub fn status(&self) -> CheckStatus {
+        self.status
+    }
+
+    /// What was found.
+    pub fn detail(&self) -> &str {
+        &self.detail
+    }
+
+    /// How to fix a warning or failure.
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+}
+
+/// Result of [`doctor`] or [`Launcher::doctor`](crate::Launcher::doctor).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DoctorReport {
+    checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// All checks, in the order they ran.
+    pub fn checks(&self) -> &[DoctorCheck] {
+        &self.checks
+    }
+
+    /// Worst status of all checks.
+    pub fn status(&self) -> CheckStatus {
+        self.checks
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(CheckStatus::Pass)
+    }
+
+    /// Checks that warned or failed.
+    pub fn problems(&self) -> impl Iterator<Item = &DoctorCheck> {
+        self.checks.iter().filter(|c| c.status != CheckStatus::Pass)
+    }
+}
+
+/// Check the environment for the best detected Arma 3 install.
+///
+/// Covers Steam detection, the Arma 3 install and, for Proton installs, the compatibility
+/// tool, Proton prefix and runtime libraries. Use [`Launcher::doctor`](crate::Launcher::doctor)
+/// to check a specific install, its mods and compatibility tool override.
+pub fn doctor() -> DoctorReport {
+    let install = detect_best_install();
+    let steam = match &install {
+        Some(install) => install.steam_installation(),
+        None => detect_steam_installations().into_iter().next(),
+    };
+    run(steam.as_ref(), install.as_ref(), &[], None)
+}
+
+pub(crate) fn run(
+    steam: Option<&SteamInstallation>,
+    install: Option<&Arma3Install>,
+    mods: &[LocalMod],
+    compat_tool: Option<&str>,
+) -> DoctorReport {
+    let mut checks = Vec::new();
+
+    match steam {
+        Some(steam) => {
+            checks.push(DoctorCheck::pass(
+                "steam root",
+                steam.root().display().to_string(),
+            ));
+            let libraries = steam.libraries();
+            checks.push(if libraries.is_empty() {
+                DoctorCheck::warn(
+                    "steam libraries",
+                    "no library with a steamapps directory found",
+                    "Start Steam once so it creates its library folders.",
+                )
+            } else {
+                let list: Vec<String> = libraries.iter().map(|l| l.display().to_string()).collect();
+                DoctorCheck::pass("steam libraries", list.join(", "))
+            });
+        }
+        None => checks.push(DoctorCheck::fail(
+            "steam root",
+            "Steam installation not found",
+            "Install Steam, or set STEAM_ROOT to its directory.",
+        )),
+    }
+    if let Some(steam) = steam {
+        checks.push(DoctorCheck::pass(
+            "steam package",
+            match steam.package() {
+                SteamPackage::Native => "native",
+                SteamPackage::Flatpak => "Flatpak (com.valvesoftware.Steam)",
+                SteamPackage::Snap => "Snap (steam)",
+                SteamPackage::Custom => "custom root (STEAM_ROOT)",
+            },
+        ));
+    }
+
+    let Some(install) = install else {
+        checks.push(DoctorCheck::fail(
+            "arma 3 install",
+            "no Arma 3 install found",
+            "Install Arma 3 through Steam, or pass its game directory explicitly.",
+        ));
+        return DoctorReport { checks };
+    };
+    checks.push(DoctorCheck::pass(
+        "arma 3 install",
+        format!(
+            "{} ({:?}, {})",
+            install.game_dir().display(),
+            install.kind(),
+            install
+                .executable()
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+        ),
+    ));
+
+    #[cfg(target_os = "linux")]
+    if install.is_proton() {
+        if let Some(steam) = steam {
+            proton_checks(&mut checks, steam.root(), install, compat_tool);
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = compat_tool;
+
+    checks.push(mod_check(mods));
+    #[cfg(target_os = "linux")]
+    if steam.is_some_and(|s| s.package() == SteamPackage::Snap) && !mods.is_empty() {
+        checks.push(snap_mod_check(install, mods));
+    }
+    DoctorReport { checks }
+}
+
+#[cfg(target_os = "linux")]
+fn proton_checks(
+    checks: &mut Vec<DoctorCheck>,
+    steam_root: &Path,
+    install: &Arma3Install,
+    compat_tool: Option<&str>,
+) {
+    use crate::steam::{compat, runtime, ARMA3_APP_ID_STR};
+
+    let ctx = install.context();
+
+    match (
+        compat_tool,
+        compat::mapped_compat_tool_name(steam_root, ARMA3_APP_ID_STR),
+    ) {
+        (Some(name), _) => checks.push(DoctorCheck::pass(
+            "compat tool mapping",
+            format!("overridden by the launcher: {name}"),
+        )),
+        (None, Ok(Some(name))) => checks.push(DoctorCheck::pass("compat tool mapping", name)),
+        (None, Ok(None)) => checks.push(DoctorCheck::warn(
+            "compat tool mapping",
+            "no tool selected for Arma 3; the newest official Proton is used",
+            "Pick a Proton version under Arma 3 > Properties > Compatibility in Steam.",
+        )),
+        (None, Err(e)) => checks.push(DoctorCheck::fail(
+            "compat tool mapping",
+            e.to_string(),
+            "Check that Steam's config/config.vdf is readable; restart Steam to rewrite it.",
+        )),
+    }
+
+    let tool = match compat::resolve_compat_tool(ctx, steam_root, ARMA3_APP_ID_STR, compat_tool) {
+        Ok(tool) => {
+            checks.push(DoctorCheck::pass(
+                "compat tool directory",
+                format!("{} ({})", tool.display_name(), tool.dir().display()),
+            ));
+            tool
+        }
+        Err(e) => {
+            checks.push(DoctorCheck::fail(
+                "compat tool directory",
+                e.to_string(),
+                "Install the selected Proton version in Steam, or unpack the custom tool into compatibilitytools.d.",
+            ));
+            return;
+        }
+    };
+
+    let mut in_runtime_container = false;
+    match compat::read_toolmanifest(tool.dir()) {
+        Ok(manifest) => match manifest.require_tool_appid {
+            Some(app_id) => match compat::required_tool_dir(steam_root, &app_id) {
+                Some(dir) => {
+                    in_runtime_container = true;
+                    checks.push(DoctorCheck::pass(
+                        "toolmanifest",
+                        format!("runs in the Steam Linux Runtime at {}", dir.display()),
+                    ));
+                }
+                None => checks.push(DoctorCheck::fail(
+                    "toolmanifest",
+                    format!("the tool requires app {app_id}, which is not installed"),
+                    "Install the Steam Linux Runtime (e.g. \"Steam Linux Runtime 3.0 (sniper)\") from the Steam library.",
+                )),
+            },
+            None => checks.push(DoctorCheck::pass("toolmanifest", manifest.commandline)),
+        },
+        Err(e) => checks.push(DoctorCheck::fail(
+            "toolmanifest",
+            e.to_string(),
+            "Reinstall the compatibility tool; its toolmanifest.vdf is missing or broken.",
+        )),
+    }
+
+    if let Some(compat_data) = install.compat_data_dir() {
+        let prefix = compat_data.join("pfx");
+        checks.push(if prefix.is_dir() {
+            DoctorCheck::pass("proton prefix", prefix.display().to_string())
+        } else {
+            DoctorCheck::warn(
+                "proton prefix",
+                format!("{} does not exist yet", prefix.display()),
+                "Start Arma 3 once from Steam so Proton creates the prefix.",
+            )
+        });
+    }
+
+    checks.push(match runtime::linux_overlay_so(steam_root) {
+        Some(so) => DoctorCheck::pass("steam overlay", so.display().to_string()),
+        None => DoctorCheck::warn(
+            "steam overlay",
+            "gameoverlayrenderer.so not found; the overlay is unavailable in direct launches",
+            "Let Steam finish updating; the library ships with the Steam client.",
+        ),
+    });
+
+    checks.push(if in_runtime_container {
+        DoctorCheck::pass("runtime libraries", "provided by the Steam Linux Runtime")
+    } else if !runtime::missing_libpng12(ctx) {
+        DoctorCheck::pass("runtime libraries", "libpng12 is installed")
+    } else if let Some(run_sh) = runtime::steam_runtime_runsh(steam_root) {
+        DoctorCheck::pass(
+            "runtime libraries",
+            format!("libpng12 is missing; using {}", run_sh.display()),
+        )
+    } else {
+        DoctorCheck::fail(
+            "runtime libraries",
+            "libpng12 is missing and the Steam runtime run.sh was not found",
+            "Install libpng12 from your distribution, or a Proton version that runs in the Steam Linux Runtime.",
+        )
+    });
+}
+
+fn mod_check(mods: &[LocalMod]) -> DoctorCheck {
+    let invalid: Vec<String> = mods
+        .iter()
+        .filter(|m| validate_local_mod_dir(m.path()).is_err())
+        .map(|m| m.path().display().to_string())
+        .collect();
+    if invalid.is_empty() {
+        DoctorCheck::pass("mods", format!("{} mods valid", mods.len()))
+    } else {
+        DoctorCheck::fail(
+            "mods",
+            format!("invalid mod directories: {}", invalid.join(", ")),
+            "Each mod needs a non-empty addons directory; re-download or re-subscribe to the mod.",
+        )
+    }
+}
+
+/// Mods a game started by Snap Steam cannot read.
+#[cfg(target_os = "linux")]
+fn snap_mod_check(install: &Arma3Install, mods: &[LocalMod]) -> DoctorCheck {
+    let blocked: Vec<String> = mods
+        .iter()
+        .filter_map(|m| {
+            let problem = snap::snap_access_problem(install.context(), m.path())?;
+            Some(format!("{} ({problem})", m.path().display()))
+        })
+        .collect();
+    if blocked.is_empty() {
+        DoctorCheck::pass("snap mod access", "all mods are visible to Snap Steam")
+    } else {
+        DoctorCheck::warn(
+            "snap mod access",
+            format!("mods outside the Snap sandbox: {}", blocked.join(", ")),
+            "Move the mods to a non-hidden directory in your home folder, or connect the removable-media interface for /media and /mnt.",
+        )
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn reports_missing_runtime_and_prefix() {
+        let d = tempfile::tempdir().unwrap();
+        let root = d.path().join("Steam");
+        let tool = root.join("compatibilitytools.d/GE-Proton9-20");
+        fs::create_dir_all(&tool).unwrap();
+        fs::write(
+            tool.join("toolmanifest.vdf"),
+            r#""manifest" { "commandline" "/proton %verb%" "require_tool_appid" "1628350" }"#,
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::write(
+            root.join("config/config.vdf"),
+            r#""InstallConfigStore" { "Software" { "Valve" { "Steam" { "CompatToolMapping" { "107410" { "name" "GE-Proton9-20" } } } } } }"#,
+        )
+        .unwrap();
+        let game = root.join("steamapps/common/Arma 3");
+        fs::create_dir_all(&game).unwrap();
+        fs::write(game.join("arma3_x64.exe"), b"").unwrap();
+        let install = Arma3Install::new(&game).unwrap();
+
+        let report = run(
+            Some(&SteamInstallation::from_root(root, SteamPackage::Custom)),
+            Some(&install),
+            &[],
+            None,
+        );
+        let status = |name| status_of(&report, name);
+        assert_eq!(status("steam root"), Some(CheckStatus::Pass));
+        assert_eq!(status("compat tool mapping"), Some(CheckStatus::Pass));
+        assert_eq!(status("compat tool directory"), Some(CheckStatus::Pass));
+        assert_eq!(status("toolmanifest"), Some(CheckStatus::Fail));
+        assert_eq!(status("proton prefix"), Some(CheckStatus::Warn));
+        assert_eq!(status("mods"), Some(CheckStatus::Pass));
+        assert_eq!(report.status(), CheckStatus::Fail);
+        assert!(report.problems().all(|c| c.hint().is_some()));
+
+        let report = run(None, None, &[], None);
+        assert_eq!(status_of(&report, "steam root"), Some(CheckStatus::Fail));
+        assert_eq!(
+            status_of(&report, "arma 3 install"),
+            Some(CheckStatus::Fail)
+        );
+    }
+
+    fn status_of(report: &DoctorReport, name: &str) -> Option<CheckStatus> {
+        report
+            .checks()
+            .iter()
+            .find(|c| c.name() == name)
+            .map(|c| c.status())
+    }
+}
@@ -39,6 +39,17 @@ pub enum Arma3Error {
     #[error("steam installation could not be detected (required for Proton direct launch)")]
     SteamNotFound,
 
+    /// Steam is running and would overwrite the change (or conflict with it).
+    #[error("steam is running; close it before changing its configuration")]
+    SteamRunning,
+
+    /// Steam did not start, or reported that it could not launch the game.
+    #[error("steam failed to launch the game: {message}")]
+    SteamLaunchFailed {
+        /// Human-readable message (usually the Steam log line).
+        message: String,
+    },
+
     /// Steam/Proton configuration parsing error.
     #[error("steam config error: {message}")]
     SteamConfig {
@@ -53,6 +64,20 @@ pub enum Arma3Error {
         message: String,
     },
 
+    /// A launch setting cannot be honoured by the chosen launch mode or install.
+    #[error("unsupported launch option: {message}")]
+    UnsupportedOption {
+        /// Human-readable message.
+        message: String,
+    },
+
+    /// Arma 3 is already running (or starting) for this install.
+    #[error("arma 3 is already running for this install (pid {pid})")]
+    AlreadyRunning {
+        /// Process holding the install: the game, a server, or a launcher starting it.
+        pid: u32,
+    },
+
     /// Spawn failed.
     #[error("failed to spawn process: {message}")]
     Spawn {
@@ -1,10 +1,15 @@
 use crate::error::{Arma3Error, Result};
-use crate::install::InstallKind;
+use crate::install::{DetectionContext, InstallKind};
 use std::path::{Path, PathBuf};
 
 const PROTON_ARMA3_CFG_REL: &str = "pfx/drive_c/users/steamuser/My Documents/Arma 3/Arma3.cfg";
+const PROTON_ARMA3_RPT_REL: &str = "pfx/drive_c/users/steamuser/AppData/Local/Arma 3";
 
-pub(crate) fn default_cfg_path(kind: InstallKind, game_dir: &Path) -> Result<PathBuf> {
+pub(crate) fn default_cfg_path(
+    ctx: &DetectionContext,
+    kind: InstallKind,
+    game_dir: &Path,
+) -> Result<PathBuf> {
     match kind {
         #[cfg(target_os = "linux")]
         InstallKind::LinuxProton => Ok(proton_cfg_path_for_game_dir(game_dir)),
@@ -15,11 +20,11 @@ pub(crate) fn default_cfg_path(kind: InstallKind, game_dir: &Path) -> Result<Pat
         }),
 
         InstallKind::LinuxNative => {
-            let home = home_dir()?;
+            let home = home_dir(ctx)?;
             Ok(home.join(".local/share/bohemiainteractive/arma3/GameDocuments/Arma 3/Arma3.cfg"))
         }
         InstallKind::WindowsNative => {
-            let docs = dirs_next::document_dir().ok_or_else(|| Arma3Error::Parse {
+            let docs = document_dir(ctx).ok_or_else(|| Arma3Error::Parse {
                 message: "cannot locate Documents directory".to_string(),
             })?;
             Ok(docs.join("Arma 3").join("Arma3.cfg"))
@@ -27,22 +32,82 @@ pub(crate) fn default_cfg_path(kind: InstallKind, game_dir: &Path) -> Result<Pat
     }
 }
 
+pub(crate) fn default_rpt_dir(
+    ctx: &DetectionContext,
+    kind: InstallKind,
+    game_dir: &Path,
+) -> Result<PathBuf> {
+    match kind {
+        #[cfg(target_os = "linux")]
+        InstallKind::LinuxProton => {
+            Ok(compatdata_dir_for_game_dir(game_dir).join(PROTON_ARMA3_RPT_REL))
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        InstallKind::LinuxProton => Err(Arma3Error::Parse {
+            message: "LinuxProton RPT path is only supported on Linux builds".to_string(),
+        }),
+
+        InstallKind::LinuxNative => {
+            let home = home_dir(ctx)?;
+            Ok(home.join(".local/share/bohemiainteractive/arma3"))
+        }
+        InstallKind::WindowsNative => {
+            let local = data_local_dir(ctx).ok_or_else(|| Arma3Error::Parse {
+                message: "cannot locate local AppData directory".to_string(),
+            })?;
+            Ok(local.join("Arma 3"))
+        }
+    }
+}
+
+/// Proton prefix parent (`steamapps/compatdata/107410`) for Proton installs.
+#[cfg(target_os = "linux")]
+pub(crate) fn compat_data_dir(kind: InstallKind, game_dir: &Path) -> Option<PathBuf> {
+    (kind == InstallKind::LinuxProton).then(|| compatdata_dir_for_game_dir(game_dir))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn compat_data_dir(_kind: InstallKind, _game_dir: &Path) -> Option<PathBuf> {
+    None
+}
+
 #[cfg(target_os = "linux")]
-fn home_dir() -> Result<PathBuf> {
-    std::env::var_os("HOME")
-        .map(PathBuf::from)
-        .ok_or_else(|| Arma3Error::Parse {
-            message: "HOME environment variable is not set".to_string(),
-        })
+fn home_dir(ctx: &DetectionContext) -> Result<PathBuf> {
+    ctx.home_dir().ok_or_else(|| Arma3Error::Parse {
+        message: "HOME environment variable is not set".to_string(),
+    })
 }
 
 #[cfg(not(target_os = "linux"))]
-fn home_dir() -> Result<PathBuf> {
+fn home_dir(_ctx: &DetectionContext) -> Result<PathBuf> {
     Err(Arma3Error::Parse {
         message: "home_dir() is not supported on this platform".to_string(),
     })
 }
 
+/// `Documents` under an overridden home, otherwise the shell folder.
+fn document_dir(ctx: &DetectionContext) -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    if ctx.has_home_override() {
+        return ctx.home_dir().map(|home| home.join("Documents"));
+    }
+    let _ = ctx;
+    dirs_next::document_dir()
+}
+
+/// `AppData/Local` under an overridden home, otherwise the shell folder.
+fn data_local_dir(ctx: &DetectionContext) -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    if ctx.has_home_override() {
+        return ctx
+            .home_dir()
+            .map(|home| home.join("AppData").join("Local"));
+    }
+    let _ = ctx;
+    dirs_next::data_local_dir()
+}
+
 #[cfg(target_os = "linux")]
 fn proton_cfg_path_for_game_dir(game_dir: &Path) -> PathBuf {
     compatdata_dir_for_game_dir(game_dir).join(PROTON_ARMA3_CFG_REL)
@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path, PathBuf};
+
+/// Where Steam and Arma 3 detection looks: home directory, environment variables, filesystem
+/// root and additional Steam roots.
+///
+/// The default reads the current process environment and the real filesystem. Installs found
+/// or created with a context ([`Arma3Install::with_context`](crate::Arma3Install::with_context))
+/// keep it, so launch planning for them uses the same lookups.
+///
+/// ```no_run
+/// use arma3_launcher::DetectionContext;
+///
+/// let ctx = DetectionContext::isolated()
+///     .home("/tmp/fake-home")
+///     .env("STEAM_ROOT", "/tmp/fake-home/.steam/steam");
+/// let installs = ctx.detect_install_candidates();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectionContext {
+    home: Option<PathBuf>,
+    env: BTreeMap<OsString, Option<OsString>>,
+    inherit_env: bool,
+    root: Option<PathBuf>,
+    extra_steam_roots: Vec<PathBuf>,
+}
+
+impl Default for DetectionContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DetectionContext {
+    /// Use the current process environment and the real filesystem.
+    pub fn new() -> Self {
+        Self {
+            home: None,
+            env: BTreeMap::new(),
+            inherit_env: true,
+            root: None,
+            extra_steam_roots: Vec::new(),
+        }
+    }
+
+    /// Ignore the process environment (including `HOME`) and the Windows registry; only values
+    /// set on the context are seen.
+    pub fn isolated() -> Self {
+        Self {
+            inherit_env: false,
+            ..Self::new()
+        }
+    }
+
+    /// Home directory (default: `HOME` from the context's environment).
+    pub fn home(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.home = Some(dir.into());
+        self
+    }
+
+    /// Set an environment variable as seen by detection (e.g. `STEAM_ROOT`, `ARMA3_DIR`, or
+    /// `XDG_CACHE_HOME` for the instance lock directory).
+    pub fn env(mut self, key: impl AsRef<OsStr>, val: impl AsRef<OsStr>) -> Self {
+        self.env.insert(
+            key.as_ref().to_os_string(),
+            Some(val.as_ref().to_os_string()),
+        );
+        self
+    }
+
+    /// Hide an environment variable from detection.
+    pub fn env_remove(mut self, key: impl AsRef<OsStr>) -> Self {
+        self.env.insert(key.as_ref().to_os_string(), None);
+        self
+    }
+
+    /// Resolve fixed system paths (`/usr/lib/...`, `C:\Program Files\...`) under `prefix`.
+    pub fn root(mut self, prefix: impl Into<PathBuf>) -> Self {
+        self.root = Some(prefix.into());
+        self
+    }
+
+    /// Also consider `dir` as a Steam root, after `STEAM_ROOT` and before default locations.
+    pub fn extra_steam_root(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.extra_steam_roots.push(dir.into());
+        self
+    }
+
+    /// Environment variable as seen by detection.
+    pub(crate) fn var_os(&self, key: &str) -> Option<OsString> {
+        match self.env.get(OsStr::new(key)) {
+            Some(value) => value.clone(),
+            None if self.inherit_env => std::env::var_os(key),
+            None => None,
+        }
+    }
+
+    pub(crate) fn home_dir(&self) -> Option<PathBuf> {
+        self.home
+            .clone()
+            .or_else(|| self.var_os("HOME").map(PathBuf::from))
+    }
+
+    /// True if `home` was set explicitly rather than taken from the environment.
+    #[cfg(target_os = "windows")]
+    pub(crate) fn has_home_override(&self) -> bool {
+        self.home.is_some()
+    }
+
+    /// `path` (absolute) under the filesystem root prefix, if any.
+    pub(crate) fn system_path(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        match &self.root {
+            Some(root) => root.join(
+                path.components()
+                    .filter(|c| matches!(c, Component::Normal(_)))
+                    .collect::<PathBuf>(),
+            ),
+            None => path.to_path_buf(),
+        }
+    }
+
+    /// Per-user cache directory (`XDG_CACHE_HOME`, else `~/.cache` on Linux).
+    pub(crate) fn cache_dir(&self) -> Option<PathBuf> {
+        #[cfg(target_os = "linux")]
+        {
+            self.var_os("XDG_CACHE_HOME")
+                .map(PathBuf::from)
+                .filter(|p| p.is_absolute())
+                .or_else(|| self.home_dir().map(|home| home.join(".cache")))
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            #[cfg(target_os = "windows")]
+            if self.has_home_override() {
+                return self
+                    .home_dir()
+                    .map(|home| home.join("AppData").join("Local"));
+            }
+            dirs_next::cache_dir()
+        }
+    }
+
+    pub(crate) fn extra_steam_roots(&self) -> &[PathBuf] {
+        &self.extra_steam_roots
+    }
+
+    /// True if machine-wide state outside the filesystem (the Windows registry) may be read.
+    #[cfg(target_os = "windows")]
+    pub(crate) fn uses_system_state(&self) -> bool {
+        self.inherit_env && self.root.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_environment_and_root() {
+        let ctx = DetectionContext::isolated()
+            .env("STEAM_ROOT", "/steam")
+            .root("/sandbox");
+        assert_eq!(ctx.var_os("STEAM_ROOT"), Some(OsString::from("/steam")));
+        assert_eq!(ctx.var_os("PATH"), None);
+        assert_eq!(ctx.home_dir(), None);
+        assert_eq!(
+            ctx.system_path("/usr/lib/libpng12.so"),
+            Path::new("/sandbox/usr/lib/libpng12.so")
+        );
+
+        let ctx = DetectionContext::new().env_remove("PATH").home("/h");
+        assert_eq!(ctx.var_os("PATH"), None);
+        assert_eq!(ctx.home_dir(), Some(PathBuf::from("/h")));
+        assert_eq!(ctx.system_path("/usr"), Path::new("/usr"));
+    }
+}
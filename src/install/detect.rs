@@ -1,4 +1,7 @@
-use crate::install::Arma3Install;
+use crate::error::Arma3Error;
+use crate::install::trace::{missing_reason, DetectionTrace};
+use crate::install::{Arma3Install, DetectionContext};
+use crate::steam::installation::{steam_installations_traced, SteamInstallation};
 use std::collections::BTreeSet;
 use std::path::PathBuf;
 
@@ -10,52 +13,81 @@ const ARMA3_STEAM_GAME_DIR_NAME: &str = "Arma 3";
 /// - `ARMA3_DIR` env override (if set and valid)
 /// - Steam libraries (`libraryfolders.vdf`) when Steam is detected
 /// - OS-specific default Steam locations (if valid)
+///
+/// Use [`trace_install_detection`] to see why paths were skipped.
 pub fn detect_install_candidates() -> Vec<Arma3Install> {
-    let mut out: Vec<Arma3Install> = Vec::new();
-    let mut seen: BTreeSet<PathBuf> = BTreeSet::new();
+    DetectionContext::new().detect_install_candidates()
+}
 
-    if let Some(p) = std::env::var_os("ARMA3_DIR").map(PathBuf::from) {
-        if let Ok(install) = Arma3Install::new(p.clone()) {
-            if seen.insert(p) {
-                out.push(install);
-            }
-        }
+/// Return the single most likely Arma 3 install (best-effort).
+pub fn detect_best_install() -> Option<Arma3Install> {
+    DetectionContext::new().detect_best_install()
+}
+
+/// Run [`detect_install_candidates`] and record every path it probes (including Steam root
+/// and library detection) with the reason it was accepted or rejected.
+pub fn trace_install_detection() -> DetectionTrace {
+    DetectionContext::new().trace_install_detection()
+}
+
+impl DetectionContext {
+    /// [`detect_install_candidates`] using this context.
+    pub fn detect_install_candidates(&self) -> Vec<Arma3Install> {
+        install_candidates(self, &mut DetectionTrace::default())
     }
 
-    for lib in crate::steam::library::detect_steam_library_roots() {
-        let game_dir = lib
-            .join("steamapps")
-            .join("common")
-            .join(ARMA3_STEAM_GAME_DIR_NAME);
-        if let Ok(install) = Arma3Install::new(game_dir.clone()) {
-            if seen.insert(game_dir) {
-                out.push(install);
-            }
+    /// [`detect_best_install`] using this context.
+    pub fn detect_best_install(&self) -> Option<Arma3Install> {
+        self.detect_install_candidates().into_iter().next()
+    }
+
+    /// [`trace_install_detection`] using this context.
+    pub fn trace_install_detection(&self) -> DetectionTrace {
+        let mut trace = DetectionTrace::default();
+        install_candidates(self, &mut trace);
+        trace
+    }
+}
+
+fn install_candidates(ctx: &DetectionContext, trace: &mut DetectionTrace) -> Vec<Arma3Install> {
+    let mut candidates = Candidates {
+        ctx,
+        out: Vec::new(),
+        seen: BTreeSet::new(),
+    };
+
+    if let Some(p) = ctx.var_os("ARMA3_DIR").map(PathBuf::from) {
+        candidates.probe(trace, "ARMA3_DIR", p);
+    }
+
+    for steam in steam_installations_traced(ctx, trace) {
+        for lib in steam.libraries() {
+            let game_dir = lib
+                .join("steamapps")
+                .join("common")
+                .join(ARMA3_STEAM_GAME_DIR_NAME);
+            candidates.probe_owned(trace, "steam library", game_dir, Some(&steam));
         }
     }
 
     #[cfg(target_os = "windows")]
     {
         let defaults = [
-            PathBuf::from(r"C:\Program Files (x86)\Steam")
+            ctx.system_path(r"C:\Program Files (x86)\Steam")
                 .join("steamapps\\common")
                 .join(ARMA3_STEAM_GAME_DIR_NAME),
-            PathBuf::from(r"C:\Program Files\Steam")
+            ctx.system_path(r"C:\Program Files\Steam")
                 .join("steamapps\\common")
                 .join(ARMA3_STEAM_GAME_DIR_NAME),
         ];
         for p in defaults {
-            if let Ok(install) = Arma3Install::new(p.clone()) {
-                if seen.insert(p) {
-                    out.push(install);
-                }
-            }
+            candidates.probe(trace, "default location", p);
         }
     }
 
     #[cfg(target_os = "linux")]
     {
-        if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        if let Some(home) = ctx.home_dir() {
             let roots = [
                 home.join(".steam/steam"),
                 home.join(".steam/root"),
@@ -69,19 +101,57 @@ pub fn detect_install_candidates() -> Vec<Arma3Install> {
                     .join("steamapps")
                     .join("common")
                     .join(ARMA3_STEAM_GAME_DIR_NAME);
-                if let Ok(install) = Arma3Install::new(p.clone()) {
-                    if seen.insert(p) {
-                        out.push(install);
-                    }
-                }
+                candidates.probe(trace, "default location", p);
             }
         }
     }
 
-    out
+    candidates.out
 }
 
-/// Return the single most likely Arma 3 install (best-effort).
-pub fn detect_best_install() -> Option<Arma3Install> {
-    detect_install_candidates().into_iter().next()
+struct Candidates<'a> {
+    ctx: &'a DetectionContext,
+    out: Vec<Arma3Install>,
+    seen: BTreeSet<PathBuf>,
+}
+
+impl Candidates<'_> {
+    fn probe(&mut self, trace: &mut DetectionTrace, source: &str, game_dir: PathBuf) {
+        self.probe_owned(trace, source, game_dir, None);
+    }
+
+    /// Probe a game directory in a library of `steam`.
+    fn probe_owned(
+        &mut self,
+        trace: &mut DetectionTrace,
+        source: &str,
+        game_dir: PathBuf,
+        steam: Option<&SteamInstallation>,
+    ) {
+        if self.seen.contains(&game_dir) {
+            trace.reject(source, &game_dir, "already found");
+            return;
+        }
+        match Arma3Install::with_context(game_dir.clone(), self.ctx.clone()) {
+            Ok(mut install) => {
+                if let Some(steam) = steam {
+                    install.steam = Some(steam.clone());
+                }
+                trace.accept(
+                    source,
+                    &game_dir,
+                    format!("Arma 3 install ({:?})", install.kind()),
+                );
+                self.seen.insert(game_dir);
+                self.out.push(install);
+            }
+            Err(Arma3Error::ExecutableNotFound { .. }) => {
+                trace.reject(source, &game_dir, "no Arma 3 executable")
+            }
+            Err(e) => {
+                let reason = missing_reason(&game_dir, &e.to_string());
+                trace.reject(source, &game_dir, reason)
+            }
+        }
+    }
 }
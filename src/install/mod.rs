@@ -1,10 +1,22 @@
 use crate::error::{Arma3Error, Result};
+use crate::steam::installation::owning_installation;
+use crate::steam::SteamInstallation;
+use std::ffi::{OsStr, OsString};
 use std::path::{Path, PathBuf};
 
 mod cfg_path;
+mod context;
 mod detect;
+pub(crate) mod trace;
 
-pub use detect::{detect_best_install, detect_install_candidates};
+pub use context::DetectionContext;
+pub use detect::{detect_best_install, detect_install_candidates, trace_install_detection};
+pub use trace::{DetectionTrace, Probe};
+
+/// Game executable names, on any platform.
+const GAME_EXE_NAMES: [&str; 3] = ["arma3.x86_64", "arma3_x64.exe", "arma3.exe"];
+/// Arma 3 launcher executable names, on any platform.
+const LAUNCHER_EXE_NAMES: [&str; 2] = ["arma3launcher.exe", "arma3launcher"];
 
 /// Platform/runtime kind for this install.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -23,11 +35,19 @@ pub struct Arma3Install {
     game_dir: PathBuf,
     executable: PathBuf,
     kind: InstallKind,
+    context: DetectionContext,
+    steam: Option<SteamInstallation>,
 }
 
 impl Arma3Install {
     /// Create and validate an install from a game directory.
     pub fn new(game_dir: impl Into<PathBuf>) -> Result<Self> {
+        Self::with_context(game_dir, DetectionContext::new())
+    }
+
+    /// Like [`new`](Self::new), but Steam, Proton and profile paths for this install are
+    /// looked up through `context`.
+    pub fn with_context(game_dir: impl Into<PathBuf>, context: DetectionContext) -> Result<Self> {
         let game_dir = game_dir.into();
         if !game_dir.is_dir() {
             return Err(Arma3Error::InvalidInstallDir { path: game_dir });
@@ -42,9 +62,32 @@ impl Arma3Install {
             game_dir,
             executable,
             kind,
+            context,
+            steam: None,
         })
     }
 
+    /// The install whose game or launcher executable appears in Steam's launch command
+    /// (`%command%`, see [`Launcher::wrap_steam_command`](crate::Launcher::wrap_steam_command)).
+    pub fn from_steam_command<I, S>(command: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let command: Vec<OsString> = command
+            .into_iter()
+            .map(|s| s.as_ref().to_os_string())
+            .collect();
+        let (index, _) = steam_command_game_arg(&command)?;
+        let game_dir =
+            Path::new(&command[index])
+                .parent()
+                .ok_or_else(|| Arma3Error::InvalidInstallDir {
+                    path: PathBuf::from(&command[index]),
+                })?;
+        Self::new(game_dir)
+    }
+
     /// Game directory (Arma 3 install).
     pub fn game_dir(&self) -> &Path {
         &self.game_dir
@@ -60,6 +103,20 @@ impl Arma3Install {
         self.kind
     }
 
+    /// Context used to look up Steam, Proton and profile paths for this install.
+    pub fn context(&self) -> &DetectionContext {
+        &self.context
+    }
+
+    /// Steam installation that owns this install: the one whose libraries contain the game
+    /// directory, otherwise the first detected installation.
+    pub fn steam_installation(&self) -> Option<SteamInstallation> {
+        match &self.steam {
+            Some(steam) => Some(steam.clone()),
+            None => owning_installation(&self.context, &self.game_dir),
+        }
+    }
+
     /// True only for Linux Proton installs.
     pub fn is_proton(&self) -> bool {
         self.kind == InstallKind::LinuxProton
@@ -71,10 +128,54 @@ impl Arma3Install {
     /// - Linux Proton: Steam compatdata prefix `.../steamapps/compatdata/107410/.../My Documents/Arma 3/Arma3.cfg`
     /// - Windows: `Documents/Arma 3/Arma3.cfg`
     pub fn default_cfg_path(&self) -> Result<PathBuf> {
-        cfg_path::default_cfg_path(self.kind, &self.game_dir)
+        cfg_path::default_cfg_path(&self.context, self.kind, &self.game_dir)
+    }
+
+    /// Directory the game writes its `.rpt` logs to.
+    ///
+    /// - Linux native: `~/.local/share/bohemiainteractive/arma3`
+    /// - Linux Proton: Steam compatdata prefix `.../pfx/drive_c/users/steamuser/AppData/Local/Arma 3`
+    /// - Windows: `AppData/Local/Arma 3`
+    pub fn rpt_dir(&self) -> Result<PathBuf> {
+        cfg_path::default_rpt_dir(&self.context, self.kind, &self.game_dir)
+    }
+
+    /// Steam `compatdata` directory holding the Proton prefix (Proton installs only).
+    pub(crate) fn compat_data_dir(&self) -> Option<PathBuf> {
+        cfg_path::compat_data_dir(self.kind, &self.game_dir)
     }
 }
 
+/// Index of the Arma 3 game or launcher executable in Steam's launch command, program included,
+/// and whether it is the launcher.
+pub(crate) fn steam_command_game_arg(command: &[OsString]) -> Result<(usize, bool)> {
+    command
+        .iter()
+        .enumerate()
+        .find_map(|(i, arg)| {
+            let arg = arg.to_string_lossy();
+            let name = arg.rsplit(['/', '\\']).next().unwrap_or_default();
+            let name = name.to_ascii_lowercase();
+            if GAME_EXE_NAMES.contains(&name.as_str()) {
+                Some((i, false))
+            } else if LAUNCHER_EXE_NAMES.contains(&name.as_str()) {
+                Some((i, true))
+            } else {
+                None
+            }
+        })
+        .ok_or_else(|| Arma3Error::Parse {
+            message: format!(
+                "Arma 3 executable not found in Steam command line: {}",
+                command
+                    .iter()
+                    .map(|a| a.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+        })
+}
+
 fn find_executable(game_dir: &Path) -> Option<(PathBuf, InstallKind)> {
     #[cfg(target_os = "linux")]
     {
@@ -0,0 +1,100 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Every path probed while detecting Steam and Arma 3, and why each was accepted or rejected.
+///
+/// Produced by [`trace_install_detection`](crate::trace_install_detection).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DetectionTrace {
+    probes: Vec<Probe>,
+}
+
+/// A single probed path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Probe {
+    source: String,
+    path: PathBuf,
+    accepted: bool,
+    reason: String,
+}
+
+impl DetectionTrace {
+    /// Probes in the order they were made.
+    pub fn probes(&self) -> &[Probe] {
+        &self.probes
+    }
+
+    pub(crate) fn accept(
+        &mut self,
+        source: impl Into<String>,
+        path: &Path,
+        reason: impl Into<String>,
+    ) {
+        self.push(source.into(), path, true, reason.into());
+    }
+
+    pub(crate) fn reject(
+        &mut self,
+        source: impl Into<String>,
+        path: &Path,
+        reason: impl Into<String>,
+    ) {
+        self.push(source.into(), path, false, reason.into());
+    }
+
+    fn push(&mut self, source: String, path: &Path, accepted: bool, reason: String) {
+        self.probes.push(Probe {
+            source,
+            path: path.to_path_buf(),
+            accepted,
+            reason,
+        });
+    }
+}
+
+impl Probe {
+    /// Where the path came from (e.g. `STEAM_ROOT`, `steam root candidate`, `libraryfolders.vdf`).
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Probed path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// True if the path was used.
+    pub fn accepted(&self) -> bool {
+        self.accepted
+    }
+
+    /// Why the path was accepted or rejected.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for DetectionTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for p in &self.probes {
+            let verdict = if p.accepted { "accepted" } else { "rejected" };
+            writeln!(
+                f,
+                "{verdict}: {} ({}): {}",
+                p.path.display(),
+                p.source,
+                p.reason
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// `reason`, or "does not exist" when `path` is missing altogether.
+pub(crate) fn missing_reason(path: &Path, reason: &str) -> String {
+    if path.exists() {
+        reason.to_string()
+    } else {
+        "does not exist".to_string()
+    }
+}
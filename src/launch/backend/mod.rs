@@ -2,23 +2,35 @@ use crate::error::Result;
 use crate::install::Arma3Install;
 use crate::launch::env::EnvVars;
 use crate::launch::plan::CommandSpec;
+use crate::launch::proton_options::ProtonOptions;
 use std::ffi::OsString;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub(crate) mod direct;
 pub(crate) mod proton;
 pub(crate) mod steam;
+pub(crate) mod steam_url;
+pub(crate) mod wrap;
 
 pub(crate) struct BackendParams<'a> {
     pub(crate) install: &'a Arma3Install,
     pub(crate) user_args: &'a [OsString],
     pub(crate) user_env: &'a EnvVars,
     pub(crate) working_dir: Option<&'a Path>,
-    pub(crate) disable_esync: bool,
+    /// Host paths the game reads besides the install (mod directories, `-par` file).
+    pub(crate) host_paths: &'a [PathBuf],
+    pub(crate) proton: &'a ProtonOptions,
+    pub(crate) compat_tool: Option<&'a str>,
+    pub(crate) url_opener: Option<&'a Path>,
 }
 
 pub(crate) trait Backend {
     fn plan(&self, params: &BackendParams<'_>) -> Result<CommandSpec>;
+
+    /// Non-fatal problems with the plan the user should know about.
+    fn warnings(&self, _params: &BackendParams<'_>) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 pub(crate) fn collect_env(user_env: &EnvVars) -> Vec<(OsString, OsString)> {
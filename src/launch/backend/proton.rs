@@ -1,9 +1,13 @@
 use crate::error::{Arma3Error, Result};
 use crate::launch::backend::{collect_env, Backend, BackendParams};
 use crate::launch::plan::CommandSpec;
+use crate::launch::EnvVars;
 use crate::steam::{
-    compat, detect, runtime, ARMA3_APP_ID_STR, ENV_PROTON_NO_ESYNC, ENV_STEAM_COMPAT_DATA_PATH,
-    ENV_STEAM_GAME_ID,
+    compat, library, runtime, SteamInstallation, SteamPackage, ARMA3_APP_ID_STR, ENV_STEAM_APP_ID,
+    ENV_STEAM_COMPAT_APP_ID, ENV_STEAM_COMPAT_CLIENT_INSTALL_PATH, ENV_STEAM_COMPAT_DATA_PATH,
+    ENV_STEAM_COMPAT_INSTALL_PATH, ENV_STEAM_COMPAT_LIBRARY_PATHS, ENV_STEAM_COMPAT_SHADER_PATH,
+    ENV_STEAM_COMPAT_TOOL_PATHS, ENV_STEAM_GAME_ID, ENV_WINEDLLOVERRIDES, FLATPAK_STEAM_APP_ID,
+    VERB_RUN, VERB_WAIT_FOR_EXIT_AND_RUN,
 };
 use std::ffi::OsString;
 use std::path::{Path, PathBuf};
@@ -30,42 +34,122 @@ impl Backend for ProtonBackend {
 
 #[cfg(target_os = "linux")]
 fn build_proton_direct_spec(params: &BackendParams<'_>) -> Result<CommandSpec> {
-    let steam_root = detect::detect_steam_root().ok_or(Arma3Error::SteamNotFound)?;
+    let steam = params
+        .install
+        .steam_installation()
+        .ok_or(Arma3Error::SteamNotFound)?;
+    if steam.package() != SteamPackage::Flatpak {
+        return build_proton_spec_for_root(params, steam.root());
+    }
 
-    let shortname = compat::compat_tool_shortname(&steam_root, ARMA3_APP_ID_STR)?;
-    let tool_dir = compat::compat_tool_dir(&steam_root, &shortname).ok_or_else(|| {
-        Arma3Error::SteamConfig {
-            message: format!("cannot find compatibility tool directory for '{shortname}'"),
-        }
-    })?;
+    if params.install.context().var_os("FLATPAK_ID").is_some() {
+        return Err(Arma3Error::UnsupportedOption {
+            message: "Proton direct launch for Flatpak Steam needs `flatpak run`, which is not available inside a Flatpak sandbox; use LaunchMode::SteamUrl instead".to_string(),
+        });
+    }
+    let spec = build_proton_spec_for_root(params, steam.root())?;
+    Ok(flatpak_spec(
+        spec,
+        &steam,
+        params.host_paths,
+        params.user_env,
+    ))
+}
 
-    let cmdline = compat::toolmanifest_commandline(&tool_dir)?;
+/// Run `spec` inside the Flatpak Steam sandbox, where its compatibility tools and runtime live.
+///
+/// `flatpak run` resets variables such as `LD_PRELOAD`, so the Steam and Proton variables set
+/// here are passed with `--env`. Variables from [`Launcher::env`](crate::Launcher::env) stay in
+/// the process environment, which `flatpak run` forwards, so they never appear on a command
+/// line. Host paths outside Steam's own directories are exposed read-only.
+#[cfg(target_os = "linux")]
+fn flatpak_spec(
+    spec: CommandSpec,
+    steam: &SteamInstallation,
+    host_paths: &[PathBuf],
+    user_env: &EnvVars,
+) -> CommandSpec {
+    let mut args = vec![OsString::from("run"), flag("--command=", &spec.program)];
+    let steam_dirs: Vec<&Path> = std::iter::once(steam.root())
+        .chain(steam.libraries().iter().map(PathBuf::as_path))
+        .collect();
+    for path in host_paths {
+        if !steam_dirs.iter().any(|dir| path.starts_with(dir)) {
+            let mut arg = flag("--filesystem=", path);
+            arg.push(":ro");
+            args.push(arg);
+        }
+    }
+    let (env, launcher_env): (Vec<_>, Vec<_>) = spec
+        .env
+        .into_iter()
+        .partition(|(key, value)| user_env.get(key) == Some(value));
+    for (key, value) in &launcher_env {
+        let mut arg = flag("--env=", key);
+        arg.push("=");
+        arg.push(value);
+        args.push(arg);
+    }
+    args.push(OsString::from(FLATPAK_STEAM_APP_ID));
+    args.extend(spec.args);
 
-    let mut parts = shell_words::split(&cmdline).map_err(|e| Arma3Error::SteamConfig {
-        message: format!("failed parsing tool commandline: {e}"),
-    })?;
-    if parts.is_empty() {
-        return Err(Arma3Error::SteamConfig {
-            message: "empty tool commandline".into(),
-        });
+    CommandSpec {
+        program: PathBuf::from("flatpak"),
+        args,
+        cwd: spec.cwd,
+        env,
     }
+}
 
-    let tool_rel = parts.remove(0);
-    let tool_program = tool_dir.join(tool_rel);
+#[cfg(target_os = "linux")]
+fn flag(name: &str, value: impl AsRef<std::ffi::OsStr>) -> OsString {
+    let mut arg = OsString::from(name);
+    arg.push(value);
+    arg
+}
 
-    for p in &mut parts {
-        if p == "%verb%" {
-            *p = "run".to_string();
-        } else if p.contains("%verb%") {
-            *p = p.replace("%verb%", "run");
+#[cfg(target_os = "linux")]
+fn build_proton_spec_for_root(
+    params: &BackendParams<'_>,
+    steam_root: &Path,
+) -> Result<CommandSpec> {
+    let ctx = params.install.context();
+    let tool = compat::resolve_compat_tool(ctx, steam_root, ARMA3_APP_ID_STR, params.compat_tool)?;
+    let shortname = tool.name();
+    let tool_dir = tool.dir().to_path_buf();
+
+    let manifest = compat::read_toolmanifest(&tool_dir)?;
+
+    // Newer Proton builds declare the Steam Linux Runtime container they must run in.
+    let runtime = match &manifest.require_tool_appid {
+        Some(app_id) => {
+            let runtime_dir = compat::required_tool_dir(steam_root, app_id).ok_or_else(|| {
+                Arma3Error::SteamConfig {
+                    message: format!(
+                        "compatibility tool '{shortname}' requires tool appid {app_id}, which is not installed"
+                    ),
+                }
+            })?;
+            let runtime_manifest = compat::read_toolmanifest(&runtime_dir)?;
+            Some((runtime_dir, runtime_manifest))
         }
-    }
+        None => None,
+    };
+
+    // Steam uses `waitforexitandrun` for every layer of a containerized launch.
+    let verb = if runtime.is_some() {
+        VERB_WAIT_FOR_EXIT_AND_RUN
+    } else {
+        VERB_RUN
+    };
+
+    let (tool_program, tool_args) = split_tool_commandline(&tool_dir, &manifest.commandline, verb)?;
 
     let mut env = collect_env(params.user_env);
 
-    if let Some(overlay) = runtime::linux_overlay_so() {
+    if let Some(overlay) = runtime::linux_overlay_so(steam_root) {
         let mut ld_preload = overlay.to_string_lossy().to_string();
-        if let Some(old) = std::env::var_os("LD_PRELOAD") {
+        if let Some(old) = ctx.var_os("LD_PRELOAD") {
             if !old.is_empty() {
                 ld_preload.push(':');
                 ld_preload.push_str(&old.to_string_lossy());
@@ -74,29 +158,58 @@ fn build_proton_direct_spec(params: &BackendParams<'_>) -> Result<CommandSpec> {
         env.push((OsString::from("LD_PRELOAD"), OsString::from(ld_preload)));
     }
 
-    let compat_data = compatdata_dir_for_game_dir(params.install.game_dir(), &steam_root);
+    let compat_data = compatdata_dir_for_game_dir(params.install.game_dir(), steam_root);
+    let libraries = library::steam_library_roots(steam_root);
 
-    env.push((
-        OsString::from(ENV_STEAM_GAME_ID),
-        OsString::from(ARMA3_APP_ID_STR),
-    ));
-    env.push((
-        OsString::from(ENV_STEAM_COMPAT_DATA_PATH),
-        OsString::from(compat_data.to_string_lossy().to_string()),
+    let mut tool_paths = vec![tool_dir.clone()];
+    if let Some((runtime_dir, _)) = &runtime {
+        tool_paths.push(runtime_dir.clone());
+    }
+
+    env.extend(steam_compat_env(
+        steam_root,
+        params.install.game_dir(),
+        &compat_data,
+        &libraries,
+        &tool_paths,
     ));
 
-    if params.disable_esync {
-        env.push((OsString::from(ENV_PROTON_NO_ESYNC), OsString::from("1")));
-    }
+    let existing_dll_overrides = params
+        .user_env
+        .get(ENV_WINEDLLOVERRIDES)
+        .cloned()
+        .or_else(|| ctx.var_os(ENV_WINEDLLOVERRIDES));
+    env.extend(params.proton.env(existing_dll_overrides));
 
-    let mut args: Vec<OsString> = parts.into_iter().map(OsString::from).collect();
+    let mut args: Vec<OsString> = tool_args.into_iter().map(OsString::from).collect();
     args.push(OsString::from(
         params.install.executable().to_string_lossy().to_string(),
     ));
     args.extend_from_slice(params.user_args);
 
-    if missing_libpng12() {
-        if let Some(runsh) = runtime::steam_runtime_runsh() {
+    let cwd = params
+        .working_dir
+        .map(|p| p.to_path_buf())
+        .or_else(|| Some(params.install.game_dir().to_path_buf()));
+
+    if let Some((runtime_dir, runtime_manifest)) = runtime {
+        let (entry_point, entry_args) =
+            split_tool_commandline(&runtime_dir, &runtime_manifest.commandline, verb)?;
+
+        let mut wrapped_args: Vec<OsString> = entry_args.into_iter().map(OsString::from).collect();
+        wrapped_args.push(OsString::from(tool_program.to_string_lossy().to_string()));
+        wrapped_args.extend(args);
+
+        return Ok(CommandSpec {
+            program: entry_point,
+            args: wrapped_args,
+            cwd,
+            env,
+        });
+    }
+
+    if runtime::missing_libpng12(ctx) {
+        if let Some(runsh) = runtime::steam_runtime_runsh(steam_root) {
             let mut wrapped_args = Vec::with_capacity(1 + args.len());
             wrapped_args.push(OsString::from(tool_program.to_string_lossy().to_string()));
             wrapped_args.extend(args);
@@ -104,10 +217,7 @@ fn build_proton_direct_spec(params: &BackendParams<'_>) -> Result<CommandSpec> {
             return Ok(CommandSpec {
                 program: runsh,
                 args: wrapped_args,
-                cwd: params
-                    .working_dir
-                    .map(|p| p.to_path_buf())
-                    .or_else(|| Some(params.install.game_dir().to_path_buf())),
+                cwd,
                 env,
             });
         }
@@ -116,14 +226,110 @@ fn build_proton_direct_spec(params: &BackendParams<'_>) -> Result<CommandSpec> {
     Ok(CommandSpec {
         program: tool_program,
         args,
-        cwd: params
-            .working_dir
-            .map(|p| p.to_path_buf())
-            .or_else(|| Some(params.install.game_dir().to_path_buf())),
+        cwd,
         env,
     })
 }
 
+/// Environment Steam provides to compatibility tools for a game launch.
+#[cfg(target_os = "linux")]
+fn steam_compat_env(
+    steam_root: &Path,
+    game_dir: &Path,
+    compat_data: &Path,
+    libraries: &[PathBuf],
+    tool_paths: &[PathBuf],
+) -> Vec<(OsString, OsString)> {
+    let shader_cache = compat_data
+        .parent()
+        .and_then(Path::parent)
+        .map(|steamapps| steamapps.join("shadercache").join(ARMA3_APP_ID_STR));
+
+    let mut env = vec![
+        (
+            OsString::from(ENV_STEAM_GAME_ID),
+            OsString::from(ARMA3_APP_ID_STR),
+        ),
+        (
+            OsString::from(ENV_STEAM_APP_ID),
+            OsString::from(ARMA3_APP_ID_STR),
+        ),
+        (
+            OsString::from(ENV_STEAM_COMPAT_APP_ID),
+            OsString::from(ARMA3_APP_ID_STR),
+        ),
+        (
+            OsString::from(ENV_STEAM_COMPAT_DATA_PATH),
+            compat_data.as_os_str().to_os_string(),
+        ),
+        (
+            OsString::from(ENV_STEAM_COMPAT_CLIENT_INSTALL_PATH),
+            steam_root.as_os_str().to_os_string(),
+        ),
+        (
+            OsString::from(ENV_STEAM_COMPAT_INSTALL_PATH),
+            game_dir.as_os_str().to_os_string(),
+        ),
+        (
+            OsString::from(ENV_STEAM_COMPAT_LIBRARY_PATHS),
+            join_paths(libraries.iter().map(|lib| lib.join("steamapps"))),
+        ),
+        (
+            OsString::from(ENV_STEAM_COMPAT_TOOL_PATHS),
+            join_paths(tool_paths.iter().cloned()),
+        ),
+    ];
+
+    if let Some(shader_cache) = shader_cache {
+        env.push((
+            OsString::from(ENV_STEAM_COMPAT_SHADER_PATH),
+            shader_cache.into_os_string(),
+        ));
+    }
+
+    env
+}
+
+#[cfg(target_os = "linux")]
+fn join_paths(paths: impl Iterator<Item = PathBuf>) -> OsString {
+    let mut out = OsString::new();
+    for (i, p) in paths.enumerate() {
+        if i > 0 {
+            out.push(":");
+        }
+        out.push(p.as_os_str());
+    }
+    out
+}
+
+/// Split a toolmanifest commandline into the tool program and its arguments, substituting `%verb%`.
+#[cfg(target_os = "linux")]
+fn split_tool_commandline(
+    tool_dir: &Path,
+    cmdline: &str,
+    verb: &str,
+) -> Result<(PathBuf, Vec<String>)> {
+    let mut parts = shell_words::split(cmdline).map_err(|e| Arma3Error::SteamConfig {
+        message: format!("failed parsing tool commandline: {e}"),
+    })?;
+    if parts.is_empty() {
+        return Err(Arma3Error::SteamConfig {
+            message: "empty tool commandline".into(),
+        });
+    }
+
+    let tool_rel = parts.remove(0);
+    let tool_program = tool_dir.join(tool_rel.trim_start_matches('/'));
+
+    for p in &mut parts {
+        if p.contains("%verb%") {
+            *p = p.replace("%verb%", verb);
+        }
+    }
+
+    Ok((tool_program, parts))
+}
+
 #[cfg(target_os = "linux")]
 fn compatdata_dir_for_game_dir(game_dir: &Path, steam_root: &Path) -> PathBuf {
     if let Some(root) = steam_library_root_from_game_dir(game_dir) {
@@ -160,19 +366,216 @@ fn steam_library_root_from_game_dir(game_dir: &Path) -> Option<PathBuf> {
     steamapps.parent().map(|p| p.to_path_buf())
 }
 
-#[cfg(target_os = "linux")]
-fn missing_libpng12() -> bool {
-    let candidates = [
-        "/usr/lib/libpng12.so.0",
-        "/usr/lib/libpng12.so",
-        "/usr/lib64/libpng12.so.0",
-        "/usr/lib64/libpng12.so",
-        "/lib/libpng12.so.0",
-        "/lib/libpng12.so",
-        "/lib64/libpng12.so.0",
-        "/lib64/libpng12.so",
-        "/usr/lib/x86_64-linux-gnu/libpng12.so.0",
-        "/usr/lib/x86_64-linux-gnu/libpng12.so",
-    ];
-    !candidates.iter().any(|p| Path::new(p).is_file())
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::install::Arma3Install;
+    use crate::launch::env::EnvVars;
+    use crate::launch::proton_options::{DllOverride, ProtonOptions};
+    use std::fs;
+
+    /// Steam root with GE-Proton mapped for Arma and Arma installed in a second library.
+    fn fixture_steam_tree(root: &Path, library: &Path, require_runtime: bool) {
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::write(
+            root.join("config/config.vdf"),
+            r#""InstallConfigStore" { "Software" { "Valve" { "Steam" { "CompatToolMapping" { "107410" { "name" "GE-Proton9-20" } } } } } }"#,
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("steamapps")).unwrap();
+        fs::write(
+            root.join("steamapps/libraryfolders.vdf"),
+            format!(
+                r#""libraryfolders" {{ "0" {{ "path" "{}" }} "1" {{ "path" "{}" }} }}"#,
+                root.display(),
+                library.display()
+            ),
+        )
+        .unwrap();
+
+        let tool = root.join("compatibilitytools.d/GE-Proton9-20");
+        fs::create_dir_all(&tool).unwrap();
+        let require = if require_runtime {
+            r#""require_tool_appid" "1628350""#
+        } else {
+            ""
+        };
+        fs::write(
+            tool.join("toolmanifest.vdf"),
+            format!(r#""manifest" {{ "version" "2" "commandline" "/proton %verb%" {require} }}"#),
+        )
+        .unwrap();
+
+        let sniper = root.join("steamapps/common/SteamLinuxRuntime_sniper");
+        fs::create_dir_all(&sniper).unwrap();
+        fs::write(
+            sniper.join("toolmanifest.vdf"),
+            r#""manifest" { "commandline" "/_v2-entry-point --verb=%verb% --" "version" "2" }"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("steamapps/appmanifest_1628350.acf"),
+            r#""AppState" { "appid" "1628350" "installdir" "SteamLinuxRuntime_sniper" }"#,
+        )
+        .unwrap();
+
+        let game = library.join("steamapps/common/Arma 3");
+        fs::create_dir_all(&game).unwrap();
+        fs::write(game.join("arma3_x64.exe"), b"").unwrap();
+    }
+
+    fn plan_for(steam_root: &Path, install: &Arma3Install) -> CommandSpec {
+        plan_with(
+            steam_root,
+            install,
+            &EnvVars::default(),
+            &ProtonOptions::default(),
+        )
+    }
+
+    fn plan_with(
+        steam_root: &Path,
+        install: &Arma3Install,
+        user_env: &EnvVars,
+        proton: &ProtonOptions,
+    ) -> CommandSpec {
+        let user_args = vec![OsString::from("-noSplash")];
+        let params = BackendParams {
+            install,
+            user_args: &user_args,
+            user_env,
+            working_dir: None,
+            host_paths: &[],
+            proton,
+            compat_tool: None,
+            url_opener: None,
+        };
+        build_proton_spec_for_root(&params, steam_root).unwrap()
+    }
+
+    fn env_value<'a>(spec: &'a CommandSpec, key: &str) -> Option<&'a OsString> {
+        spec.env
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    #[test]
+    fn chains_steam_linux_runtime_when_required() {
+        let d = tempfile::tempdir().unwrap();
+        let root = d.path().join("Steam");
+        let library = d.path().join("Library");
+        fixture_steam_tree(&root, &library, true);
+
+        let install = Arma3Install::new(library.join("steamapps/common/Arma 3")).unwrap();
+        let spec = plan_for(&root, &install);
+        let sniper = root.join("steamapps/common/SteamLinuxRuntime_sniper");
+        let proton = root.join("compatibilitytools.d/GE-Proton9-20/proton");
+
+        assert_eq!(spec.program, sniper.join("_v2-entry-point"));
+        assert_eq!(
+            spec.args,
+            vec![
+                OsString::from("--verb=waitforexitandrun"),
+                OsString::from("--"),
+                proton.into_os_string(),
+                OsString::from("waitforexitandrun"),
+                install.executable().as_os_str().to_os_string(),
+                OsString::from("-noSplash"),
+            ]
+        );
+    }
+
+    #[test]
+    fn sets_steam_compat_environment() {
+        let d = tempfile::tempdir().unwrap();
+        let root = d.path().join("Steam");
+        let library = d.path().join("Library");
+        fixture_steam_tree(&root, &library, true);
+
+        let install = Arma3Install::new(library.join("steamapps/common/Arma 3")).unwrap();
+        let spec = plan_for(&root, &install);
+        let tool = root.join("compatibilitytools.d/GE-Proton9-20");
+        let sniper = root.join("steamapps/common/SteamLinuxRuntime_sniper");
+
+        let expected: Vec<(&str, OsString)> = vec![
+            ("SteamGameId", "107410".into()),
+            ("SteamAppId", "107410".into()),
+            ("STEAM_COMPAT_APP_ID", "107410".into()),
+            (
+                "STEAM_COMPAT_DATA_PATH",
+                library.join("steamapps/compatdata/107410").into(),
+            ),
+            ("STEAM_COMPAT_CLIENT_INSTALL_PATH", root.clone().into()),
+            ("STEAM_COMPAT_INSTALL_PATH", install.game_dir().into()),
+            (
+                "STEAM_COMPAT_LIBRARY_PATHS",
+                format!(
+                    "{}:{}",
+                    root.join("steamapps").display(),
+                    library.join("steamapps").display()
+                )
+                .into(),
+            ),
+            (
+                "STEAM_COMPAT_TOOL_PATHS",
+                format!("{}:{}", tool.display(), sniper.display()).into(),
+            ),
+            (
+                "STEAM_COMPAT_SHADER_PATH",
+                library.join("steamapps/shadercache/107410").into(),
+            ),
+        ];
+        for (key, value) in expected {
+            assert_eq!(env_value(&spec, key), Some(&value), "{key}");
+        }
+        assert!(env_value(&spec, "PROTON_NO_ESYNC").is_none());
+    }
+
+    #[test]
+    fn tool_paths_omit_runtime_when_not_required() {
+        let d = tempfile::tempdir().unwrap();
+        let root = d.path().join("Steam");
+        let library = d.path().join("Library");
+        fixture_steam_tree(&root, &library, false);
+
+        let install = Arma3Install::new(library.join("steamapps/common/Arma 3")).unwrap();
+        let spec = plan_for(&root, &install);
+        let tool = root.join("compatibilitytools.d/GE-Proton9-20");
+
+        assert_eq!(
+            env_value(&spec, "STEAM_COMPAT_TOOL_PATHS"),
+            Some(&tool.into_os_string())
+        );
+    }
+
+    #[test]
+    fn applies_proton_options() {
+        let d = tempfile::tempdir().unwrap();
+        let root = d.path().join("Steam");
+        let library = d.path().join("Library");
+        fixture_steam_tree(&root, &library, true);
+
+        let install = Arma3Install::new(library.join("steamapps/common/Arma 3")).unwrap();
+        let mut user_env = EnvVars::default();
+        user_env.insert("WINEDLLOVERRIDES", "d3d9=n");
+        let proton = ProtonOptions::new()
+            .no_fsync(true)
+            .log_dir("/tmp/proton-logs")
+            .dxvk_hud("fps")
+            .dll_override("dinput8", DllOverride::NativeThenBuiltin);
+        let spec = plan_with(&root, &install, &user_env, &proton);
+
+        for (key, value) in [
+            ("PROTON_NO_FSYNC", "1"),
+            ("PROTON_LOG", "1"),
+            ("PROTON_LOG_DIR", "/tmp/proton-logs"),
+            ("DXVK_HUD", "fps"),
+            ("WINEDLLOVERRIDES", "d3d9=n;dinput8=n,b"),
+        ] {
+            assert_eq!(env_value(&spec, key), Some(&OsString::from(value)), "{key}");
+        }
+        assert!(env_value(&spec, "PROTON_NO_ESYNC").is_none());
+    }
 }
@@ -1,9 +1,12 @@
 use crate::error::Result;
+use crate::install::Arma3Install;
 use crate::launch::backend::{collect_env, Backend, BackendParams};
 use crate::launch::plan::CommandSpec;
-use crate::steam::{ARMA3_APP_ID_STR, STEAM_ARG_APPLAUNCH, STEAM_ARG_NO_LAUNCHER};
+use crate::steam::{
+    ARMA3_APP_ID_STR, STEAM_ARG_APPLAUNCH, STEAM_ARG_NO_LAUNCHER, STEAM_ARG_SILENT,
+};
 use std::ffi::OsString;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Default, Clone, Copy)]
 pub(crate) struct SteamBackend;
@@ -12,32 +15,7 @@ impl Backend for SteamBackend {
     fn plan(&self, params: &BackendParams<'_>) -> Result<CommandSpec> {
         let env = collect_env(params.user_env);
         let mut args: Vec<OsString> = Vec::new();
-
-        #[cfg(target_os = "linux")]
-        let (program, mut prefix_args) = if crate::steam::detect::is_flatpak_steam() {
-            (
-                PathBuf::from("flatpak"),
-                vec![
-                    OsString::from("run"),
-                    OsString::from("com.valvesoftware.Steam"),
-                ],
-            )
-        } else {
-            let exe = crate::steam::detect::detect_steam_exe();
-            (exe, vec![])
-        };
-
-        #[cfg(target_os = "windows")]
-        let (program, mut prefix_args) = {
-            let exe = crate::steam::detect::detect_steam_exe();
-            (exe, vec![])
-        };
-
-        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
-        let (program, mut prefix_args) = {
-            let _ = params;
-            (PathBuf::from("steam"), vec![])
-        };
+        let (program, mut prefix_args) = steam_command(params.install);
 
         prefix_args.push(OsString::from(STEAM_ARG_APPLAUNCH));
         prefix_args.push(OsString::from(ARMA3_APP_ID_STR));
@@ -55,4 +33,192 @@ impl Backend for SteamBackend {
             env,
         })
     }
+
+    fn warnings(&self, params: &BackendParams<'_>) -> Vec<String> {
+        match params.install.steam_installation() {
+            Some(steam) => launch_option_warnings(steam.root(), params.user_args),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Steam client program and the arguments that precede Steam's own options, for the Steam
+/// installation that owns `install`.
+///
+/// Inside a Flatpak sandbox the Steam client runs on the host and is reached through
+/// `flatpak-spawn --host`, except from Steam's own sandbox, where `steam` is available directly.
+fn steam_command(install: &Arma3Install) -> (PathBuf, Vec<OsString>) {
+    let ctx = install.context();
+    let steam = install.steam_installation();
+
+    #[cfg(target_os = "linux")]
+    {
+        use crate::steam::{SteamPackage, FLATPAK_STEAM_APP_ID};
+
+        let package = match &steam {
+            Some(steam) => steam.package(),
+            None if crate::steam::detect::is_flatpak_steam(ctx) => SteamPackage::Flatpak,
+            None => SteamPackage::Native,
+        };
+        let sandbox = ctx.var_os("FLATPAK_ID");
+        if package == SteamPackage::Flatpak
+            && sandbox.as_deref() == Some(std::ffi::OsStr::new(FLATPAK_STEAM_APP_ID))
+        {
+            return (PathBuf::from("steam"), vec![]);
+        }
+
+        let (program, mut args) = match package {
+            SteamPackage::Flatpak => (
+                PathBuf::from("flatpak"),
+                vec![OsString::from("run"), OsString::from(FLATPAK_STEAM_APP_ID)],
+            ),
+            SteamPackage::Snap => (
+                PathBuf::from("snap"),
+                vec![OsString::from("run"), OsString::from("steam")],
+            ),
+            SteamPackage::Native | SteamPackage::Custom => {
+                let root = steam.as_ref().map(|s| s.root());
+                (crate::steam::detect::detect_steam_exe(ctx, root), vec![])
+            }
+        };
+        if sandbox.is_none() {
+            return (program, args);
+        }
+        args.insert(0, program.into_os_string());
+        args.insert(0, OsString::from("--host"));
+        (PathBuf::from("flatpak-spawn"), args)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let root = steam.as_ref().map(|s| s.root());
+        (crate::steam::detect::detect_steam_exe(ctx, root), vec![])
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        let _ = (ctx, steam);
+        (PathBuf::from("steam"), vec![])
+    }
+}
+
+/// Command that starts the Steam client owning `install` in the background.
+pub(crate) fn startup_command(install: &Arma3Install) -> CommandSpec {
+    let (program, mut args) = steam_command(install);
+    args.push(OsString::from(STEAM_ARG_SILENT));
+    CommandSpec {
+        program,
+        args,
+        cwd: None,
+        env: Vec::new(),
+    }
+}
+
+/// Warn when the active Steam user has Arma 3 launch options, which Steam adds to ours.
+pub(crate) fn launch_option_warnings(steam_root: &Path, user_args: &[OsString]) -> Vec<String> {
+    let Some(account) = crate::steam::users::active_account(steam_root)
+        .ok()
+        .flatten()
+    else {
+        return Vec::new();
+    };
+    let Some(options) =
+        crate::steam::users::launch_options(steam_root, account.account_id(), ARMA3_APP_ID_STR)
+            .ok()
+            .flatten()
+    else {
+        return Vec::new();
+    };
+
+    let mut warnings = vec![format!(
+        "Steam launch options for Arma 3 (\"{options}\") will be combined with these arguments"
+    )];
+    let sets_mods = |s: &str| s.starts_with("-mod=") || s.starts_with("-mods=");
+    if options.split_whitespace().any(sets_mods)
+        && user_args.iter().any(|a| sets_mods(&a.to_string_lossy()))
+    {
+        warnings.push(
+            "both Steam's launch options and this launch set -mod=; the mod list may be overridden"
+                .to_string(),
+        );
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn warns_about_steam_launch_options() {
+        let d = tempfile::tempdir().unwrap();
+        fs::create_dir_all(d.path().join("config")).unwrap();
+        fs::write(
+            d.path().join("config/loginusers.vdf"),
+            "\"users\" { \"76561197960265729\" { \"PersonaName\" \"p\" \"MostRecent\" \"1\" } }",
+        )
+        .unwrap();
+
+        assert!(launch_option_warnings(d.path(), &[]).is_empty());
+
+        fs::create_dir_all(d.path().join("userdata/1/config")).unwrap();
+        fs::write(
+            d.path().join("userdata/1/config/localconfig.vdf"),
+            "\"UserLocalConfigStore\" { \"Software\" { \"Valve\" { \"Steam\" { \"apps\" { \"107410\" { \"LaunchOptions\" \"-mod=@cba\" } } } } } }",
+        )
+        .unwrap();
+
+        let warnings = launch_option_warnings(d.path(), &[OsString::from("-mod=@ace")]);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("-mod=@cba"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reaches_host_steam_from_flatpak_sandbox() {
+        use crate::install::DetectionContext;
+        use crate::steam::FLATPAK_STEAM_APP_ID;
+
+        let d = tempfile::tempdir().unwrap();
+        let home = d.path();
+        let native = home.join(".local/share/Steam");
+        let flatpak = home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam");
+        for root in [&native, &flatpak] {
+            fs::create_dir_all(root.join("steamapps/common/Arma 3")).unwrap();
+            fs::write(root.join("steamapps/common/Arma 3/arma3.x86_64"), "").unwrap();
+        }
+        fs::write(native.join("steam.sh"), "").unwrap();
+        let command = |root: &Path, sandbox: &str| {
+            let ctx = DetectionContext::isolated()
+                .home(home)
+                .env("FLATPAK_ID", sandbox);
+            let install = Arma3Install::with_context(root.join("steamapps/common/Arma 3"), ctx);
+            let (program, args) = steam_command(&install.unwrap());
+            let args: Vec<String> = args
+                .iter()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect();
+            (program, args)
+        };
+
+        // The owning installation decides, not the sandbox the launcher runs in.
+        let (program, args) = command(&native, FLATPAK_STEAM_APP_ID);
+        assert_eq!(program, Path::new("flatpak-spawn"));
+        assert_eq!(
+            args,
+            [
+                "--host".to_string(),
+                native.join("steam.sh").display().to_string()
+            ]
+        );
+
+        let (program, args) = command(&flatpak, "org.example.Frontend");
+        assert_eq!(program, Path::new("flatpak-spawn"));
+        assert_eq!(args, ["--host", "flatpak", "run", FLATPAK_STEAM_APP_ID]);
+
+        let (program, args) = command(&flatpak, FLATPAK_STEAM_APP_ID);
+        assert_eq!(program, Path::new("steam"));
+        assert!(args.is_empty());
+    }
 }
@@ -0,0 +1,93 @@
+use crate::error::Result;
+use crate::launch::backend::{collect_env, Backend, BackendParams};
+use crate::launch::plan::CommandSpec;
+use crate::steam::{ARMA3_APP_ID_STR, STEAM_ARG_NO_LAUNCHER};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+#[cfg(target_os = "windows")]
+const DEFAULT_URL_OPENER: &str = "explorer";
+#[cfg(not(target_os = "windows"))]
+const DEFAULT_URL_OPENER: &str = "xdg-open";
+
+/// Opens `steam://run/<appid>//<args>/` with the desktop's URL handler.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct SteamUrlBackend;
+
+impl Backend for SteamUrlBackend {
+    fn plan(&self, params: &BackendParams<'_>) -> Result<CommandSpec> {
+        let program = params
+            .url_opener
+            .map(|p| p.to_path_buf())
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_URL_OPENER));
+
+        let mut args = vec![OsString::from(STEAM_ARG_NO_LAUNCHER)];
+        args.extend_from_slice(params.user_args);
+
+        Ok(CommandSpec {
+            program,
+            args: vec![OsString::from(steam_run_url(ARMA3_APP_ID_STR, &args))],
+            cwd: params.working_dir.map(|p| p.to_path_buf()),
+            env: collect_env(params.user_env),
+        })
+    }
+
+    fn warnings(&self, params: &BackendParams<'_>) -> Vec<String> {
+        match params.install.steam_installation() {
+            Some(steam) => super::steam::launch_option_warnings(steam.root(), params.user_args),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Build `steam://run/<app_id>//<args>/`.
+///
+/// Steam splits the decoded argument string like a command line, so arguments containing
+/// whitespace are quoted before the whole string is percent-encoded.
+pub(crate) fn steam_run_url(app_id: &str, args: &[OsString]) -> String {
+    let joined = args
+        .iter()
+        .map(|a| {
+            let a = a.to_string_lossy();
+            if a.is_empty() || a.contains(char::is_whitespace) {
+                format!("\"{a}\"")
+            } else {
+                a.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("steam://run/{app_id}//{}/", percent_encode(&joined))
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_arguments_into_run_url() {
+        let args = [
+            OsString::from("-noSplash"),
+            OsString::from("-mod=@cba;@ace"),
+            OsString::from("-name=Cpt Miller"),
+        ];
+        assert_eq!(
+            steam_run_url("107410", &args),
+            "steam://run/107410//-noSplash%20-mod%3D%40cba%3B%40ace%20%22-name%3DCpt%20Miller%22/"
+        );
+        assert_eq!(steam_run_url("107410", &[]), "steam://run/107410///");
+    }
+}
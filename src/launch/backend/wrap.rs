@@ -0,0 +1,59 @@
+use crate::error::Result;
+use crate::install::steam_command_game_arg;
+use crate::launch::backend::{collect_env, Backend, BackendParams};
+use crate::launch::plan::CommandSpec;
+use crate::steam::{ENV_WINEDLLOVERRIDES, STEAM_ARG_NO_LAUNCHER};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+const PROTON_SCRIPT_NAME: &str = "proton";
+
+/// Runs Steam's own launch command (`%command%`) with our arguments spliced in after the game
+/// executable.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WrapBackend<'a> {
+    pub(crate) command: &'a [OsString],
+}
+
+impl Backend for WrapBackend<'_> {
+    fn plan(&self, params: &BackendParams<'_>) -> Result<CommandSpec> {
+        let (index, is_launcher) = steam_command_game_arg(self.command)?;
+        // On Windows `%command%` is the game (or launcher) executable itself, so the match may
+        // be the program.
+        let program = &self.command[0];
+        let mut args: Vec<OsString> = self.command[1..=index].to_vec();
+        // The Arma 3 launcher forwards arguments to the game only with -nolauncher.
+        if is_launcher && !self.command.iter().any(|a| a == STEAM_ARG_NO_LAUNCHER) {
+            args.push(OsString::from(STEAM_ARG_NO_LAUNCHER));
+        }
+        args.extend_from_slice(params.user_args);
+        args.extend_from_slice(&self.command[index + 1..]);
+
+        let mut env = collect_env(params.user_env);
+        let existing_dll_overrides = params
+            .user_env
+            .get(ENV_WINEDLLOVERRIDES)
+            .cloned()
+            .or_else(|| params.install.context().var_os(ENV_WINEDLLOVERRIDES));
+        env.extend(params.proton.env(existing_dll_overrides));
+
+        Ok(CommandSpec {
+            program: PathBuf::from(program),
+            args,
+            cwd: params.working_dir.map(|p| p.to_path_buf()),
+            env,
+        })
+    }
+}
+
+/// True if Steam's command line runs the game through a Proton script.
+pub(crate) fn runs_proton(command: &[OsString]) -> bool {
+    command
+        .iter()
+        .any(|a| file_name(&a.to_string_lossy()) == PROTON_SCRIPT_NAME)
+}
+
+/// Last path component, for either separator.
+fn file_name(arg: &str) -> &str {
+    arg.rsplit(['/', '\\']).next().unwrap_or(arg)
+}
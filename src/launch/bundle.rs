@@ -0,0 +1,304 @@
+use crate::error::Result;
+use crate::launch::{LaunchPlan, Launcher};
+use crate::rpt;
+use crate::steam::ARMA3_APP_ID_STR;
+use std::ffi::{OsStr, OsString};
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const RECENT_RPT_COUNT: usize = 3;
+const RECENT_DUMP_COUNT: usize = 2;
+const REDACTED: &str = "<redacted>";
+/// Environment variable names containing one of these are treated as secrets.
+const SECRET_ENV_MARKERS: [&str; 6] = ["TOKEN", "SECRET", "PASSWORD", "PASSWD", "AUTH", "COOKIE"];
+/// Arma 3 arguments whose value is a secret.
+const SECRET_ARGS: [&str; 2] = ["-password=", "-passwordAdmin="];
+const MOD_META_FILES: [&str; 2] = ["meta.cpp", "mod.cpp"];
+const MOD_META_KEYS: [&str; 2] = ["name", "publishedid"];
+
+impl Launcher {
+    /// Write a zip with what is needed to debug a failed launch.
+    ///
+    /// It contains the launch plan (secrets such as `-password=` and tokens redacted),
+    /// detection results (install, Steam root and libraries, compatibility tool, Proton
+    /// prefix), the mod list with metadata and sizes, the most recent RPTs, the Proton log
+    /// and the most recent crash dumps. Missing pieces are noted instead of failing.
+    pub fn write_support_bundle(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut zip = ZipWriter::new(File::create(path)?);
+        let text = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        zip.start_file("launch_plan.txt", text).map_err(zip_error)?;
+        zip.write_all(self.describe_plan().as_bytes())?;
+        zip.start_file("detection.txt", text).map_err(zip_error)?;
+        zip.write_all(self.describe_detection().as_bytes())?;
+        zip.start_file("mods.txt", text).map_err(zip_error)?;
+        zip.write_all(self.describe_mods().as_bytes())?;
+
+        if let Ok(dir) = self.install.rpt_dir() {
+            let rpts = rpt::files_with_extensions(&dir, &[rpt::RPT_EXTENSION]);
+            for (path, _) in rpts.into_iter().take(RECENT_RPT_COUNT) {
+                add_file(&mut zip, "rpt", &path, text)?;
+            }
+            let dumps = rpt::files_with_extensions(&dir, &rpt::CRASH_DUMP_EXTENSIONS);
+            for (path, _) in dumps.into_iter().take(RECENT_DUMP_COUNT) {
+                add_file(&mut zip, "crash", &path, text)?;
+            }
+        }
+        if let Some(log) = self.proton_log_path().filter(|p| p.is_file()) {
+            add_file(&mut zip, "proton", &log, text)?;
+        }
+
+        zip.finish().map_err(zip_error)?;
+        Ok(())
+    }
+
+    fn describe_plan(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "launch mode: {:?}", self.launch_mode);
+        // Redact before planning: `LaunchMode::SteamUrl` encodes the arguments into the URL.
+        let mut redacted = self.clone();
+        redacted.args = self
+            .args
+            .iter()
+            .map(|arg| match arg.to_str() {
+                Some(arg) => OsString::from(redact_arg(arg)),
+                None => arg.clone(),
+            })
+            .collect();
+        match redacted.plan() {
+            Ok(plan) => write_plan(&mut out, &plan),
+            Err(e) => {
+                let _ = writeln!(out, "planning failed: {e}");
+            }
+        }
+        out
+    }
+
+    fn describe_detection(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "arma3-launcher: {}", env!("CARGO_PKG_VERSION"));
+        let _ = writeln!(out, "os: {}", std::env::consts::OS);
+        let _ = writeln!(out, "game dir: {}", self.install.game_dir().display());
+        let _ = writeln!(out, "executable: {}", self.install.executable().display());
+        let _ = writeln!(out, "install kind: {:?}", self.install.kind());
+        write_result(&mut out, "cfg path", self.install.default_cfg_path());
+        write_result(&mut out, "rpt dir", self.install.rpt_dir());
+
+        let owner = self.install.steam_installation();
+        for steam in self.install.context().detect_steam_installations() {
+            let _ = writeln!(
+                out,
+                "steam installation: {} ({:?}){}",
+                steam.root().display(),
+                steam.package(),
+                if owner.as_ref() == Some(&steam) {
+                    ", owns this install"
+                } else {
+                    ""
+                }
+            );
+            for lib in steam.libraries() {
+                let _ = writeln!(out, "  steam library: {}", lib.display());
+            }
+        }
+        let steam_root = owner.as_ref().map(|s| s.root());
+        write_option(&mut out, "steam root", steam_root);
+
+        if self.install.is_proton() {
+            let prefix = self.install.compat_data_dir().map(|d| d.join("pfx"));
+            write_option(&mut out, "proton prefix", prefix.as_deref());
+            if let Some(root) = steam_root {
+                let tool = crate::steam::compat::resolve_compat_tool(
+                    self.install.context(),
+                    root,
+                    ARMA3_APP_ID_STR,
+                    self.compat_tool.as_deref(),
+                );
+                match tool {
+                    Ok(tool) => {
+                        let _ = writeln!(
+                            out,
+                            "compat tool: {} ({})",
+                            tool.display_name(),
+                            tool.dir().display()
+                        );
+                    }
+                    Err(e) => {
+                        let _ = writeln!(out, "compat tool: {e}");
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn describe_mods(&self) -> String {
+        let mut out = String::new();
+        if self.mods.is_empty() {
+            out.push_str("no mods enabled\n");
+        }
+        for m in self.mods.iter() {
+            let (files, bytes) = dir_size(m.path());
+            let _ = writeln!(out, "{}", m.path().display());
+            let _ = writeln!(out, "  size: {bytes} bytes in {files} files");
+            for (key, value) in mod_metadata(m.path()) {
+                let _ = writeln!(out, "  {key}: {value}");
+            }
+        }
+        out
+    }
+
+    /// Where Proton writes `steam-107410.log` when `PROTON_LOG` is set.
+    fn proton_log_path(&self) -> Option<PathBuf> {
+        if !self.install.is_proton() {
+            return None;
+        }
+        let dir = match self.proton.log_directory() {
+            Some(dir) => dir.to_path_buf(),
+            None => self.install.context().home_dir()?,
+        };
+        Some(dir.join(format!("steam-{ARMA3_APP_ID_STR}.log")))
+    }
+}
+
+fn write_plan(out: &mut String, plan: &LaunchPlan) {
+    let _ = writeln!(out, "program: {}", plan.program().display());
+    for arg in plan.args() {
+        let _ = writeln!(out, "arg: {}", redact_arg(&arg.to_string_lossy()));
+    }
+    write_option(out, "cwd", plan.cwd());
+    for (key, value) in plan.env() {
+        let _ = writeln!(
+            out,
+            "env: {}={}",
+            key.to_string_lossy(),
+            redact_env(key, &value.to_string_lossy())
+        );
+    }
+    for warning in plan.warnings() {
+        let _ = writeln!(out, "warning: {warning}");
+    }
+}
+
+fn write_result(out: &mut String, label: &str, value: Result<PathBuf>) {
+    let _ = match value {
+        Ok(path) => writeln!(out, "{label}: {}", path.display()),
+        Err(e) => writeln!(out, "{label}: {e}"),
+    };
+}
+
+fn write_option(out: &mut String, label: &str, value: Option<&Path>) {
+    let _ = match value {
+        Some(path) => writeln!(out, "{label}: {}", path.display()),
+        None => writeln!(out, "{label}: not found"),
+    };
+}
+
+fn redact_arg(arg: &str) -> String {
+    for prefix in SECRET_ARGS {
+        if let Some(head) = arg
+            .get(..prefix.len())
+            .filter(|h| h.eq_ignore_ascii_case(prefix))
+        {
+            return format!("{head}{REDACTED}");
+        }
+    }
+    arg.to_string()
+}
+
+fn redact_env(key: &OsStr, value: &str) -> String {
+    let key = key.to_string_lossy().to_ascii_uppercase();
+    if SECRET_ENV_MARKERS.iter().any(|m| key.contains(m)) {
+        REDACTED.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// `name = "..."` and `publishedid = ...` from `meta.cpp` / `mod.cpp`.
+fn mod_metadata(mod_dir: &Path) -> Vec<(String, String)> {
+    let mut out: Vec<(String, String)> = Vec::new();
+    for file in MOD_META_FILES {
+        let Ok(txt) = fs::read_to_string(mod_dir.join(file)) else {
+            continue;
+        };
+        for line in txt.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim();
+            if !MOD_META_KEYS.contains(&key) || out.iter().any(|(k, _)| k == key) {
+                continue;
+            }
+            let value = value.trim().trim_end_matches(';').trim().trim_matches('"');
+            out.push((key.to_string(), value.to_string()));
+        }
+    }
+    out
+}
+
+/// Number of files and total size under `dir`.
+fn dir_size(dir: &Path) -> (u64, u64) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (0, 0);
+    };
+    let mut total = (0, 0);
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            let (files, bytes) = dir_size(&entry.path());
+            total.0 += files;
+            total.1 += bytes;
+        } else {
+            total.0 += 1;
+            total.1 += meta.len();
+        }
+    }
+    total
+}
+
+fn add_file(
+    zip: &mut ZipWriter<File>,
+    dir: &str,
+    path: &Path,
+    options: SimpleFileOptions,
+) -> Result<()> {
+    let Some(name) = path.file_name() else {
+        return Ok(());
+    };
+    let Ok(mut file) = File::open(path) else {
+        return Ok(());
+    };
+    let options = options.large_file(file.metadata()?.len() >= u64::from(u32::MAX));
+    zip.start_file(format!("{dir}/{}", name.to_string_lossy()), options)
+        .map_err(zip_error)?;
+    io::copy(&mut file, zip)?;
+    Ok(())
+}
+
+fn zip_error(e: zip::result::ZipError) -> io::Error {
+    io::Error::other(e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_secrets() {
+        assert_eq!(redact_arg("-password=hunter2"), "-password=<redacted>");
+        assert_eq!(redact_arg("-passwordAdmin=x"), "-passwordAdmin=<redacted>");
+        assert_eq!(redact_arg("-connect=1.2.3.4"), "-connect=1.2.3.4");
+        assert_eq!(
+            redact_env(OsStr::new("STEAM_AUTH_TOKEN"), "abc"),
+            "<redacted>"
+        );
+        assert_eq!(redact_env(OsStr::new("PROTON_LOG"), "1"), "1");
+    }
+}
@@ -13,6 +13,11 @@ impl EnvVars {
             .insert(key.as_ref().to_os_string(), val.as_ref().to_os_string());
     }
 
+    /// Value for `key`, if set.
+    pub fn get(&self, key: impl AsRef<OsStr>) -> Option<&OsString> {
+        self.inner.get(key.as_ref())
+    }
+
     /// Iterate over environment variables in a stable order.
     pub fn iter(&self) -> impl Iterator<Item = (&OsString, &OsString)> {
         self.inner.iter()
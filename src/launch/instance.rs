@@ -0,0 +1,197 @@
+use crate::error::{Arma3Error, Result};
+use crate::install::Arma3Install;
+use crate::platform::process;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const LOCK_DIR_NAME: &str = "arma3-launcher";
+const SERVER_EXE_NAMES: [&str; 3] = [
+    "arma3server_x64.exe",
+    "arma3server.exe",
+    "arma3server.x86_64",
+];
+
+/// What to do when Arma 3 is already running for the same install.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InstanceCheck {
+    /// Launch regardless (default).
+    #[default]
+    Off,
+    /// Fail with [`Arma3Error::AlreadyRunning`].
+    Error,
+    /// [`Launcher::start`](crate::Launcher::start) returns a session for the running game;
+    /// [`Launcher::launch`](crate::Launcher::launch) fails as with [`InstanceCheck::Error`].
+    Attach,
+}
+
+/// Lock file held while an instance starts, then pointing at the game process.
+///
+/// It covers the gap between spawning and the game process appearing, when a `/proc` scan
+/// cannot see it yet.
+#[derive(Debug)]
+pub(crate) struct InstanceLock {
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Take the lock for `install`, or return the PID of the running instance.
+    pub(crate) fn acquire(install: &Arma3Install) -> Result<std::result::Result<Self, u32>> {
+        if let Some(pid) = running_pid(install) {
+            return Ok(Err(pid));
+        }
+
+        let path = lock_path(install)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut f) => {
+                    writeln!(f, "{}", std::process::id())?;
+                    return Ok(Ok(Self { path }));
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    match LockOwner::read(&path) {
+                        Some(owner) if owner.is_live() => return Ok(Err(owner.pid)),
+                        // Stale: replace it.
+                        _ => {
+                            let _ = fs::remove_file(&path);
+                        }
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(Arma3Error::Spawn {
+            message: format!("could not create lock file {}", path.display()),
+        })
+    }
+
+    /// Point the lock at the game process, so it stays valid after this process exits.
+    pub(crate) fn set_owner(&self, pid: u32) -> Result<()> {
+        fs::write(&self.path, format!("{pid}\n"))?;
+        Ok(())
+    }
+
+    /// Keep the lock for `timeout` on behalf of `pid` (Steam), until the game process appears
+    /// and can be found by a `/proc` scan instead.
+    pub(crate) fn set_pending(&self, pid: u32, timeout: Duration) -> Result<()> {
+        let until = (SystemTime::now() + timeout)
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        fs::write(&self.path, format!("{pid} {until}\n"))?;
+        Ok(())
+    }
+
+    /// Remove the lock file.
+    pub(crate) fn release(self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// PID of a running Arma 3 client or server from `install`.
+pub(crate) fn running_pid(install: &Arma3Install) -> Option<u32> {
+    let game_dir = install.game_dir();
+    let client = install
+        .executable()
+        .file_name()
+        .and_then(|n| n.to_str())
+        .into_iter();
+    client
+        .chain(SERVER_EXE_NAMES)
+        .find_map(|name| process::find_game_pid(name, game_dir))
+}
+
+/// Contents of a lock file: `<pid>` or `<pid> <pending until, unix seconds>`.
+struct LockOwner {
+    pid: u32,
+    pending_until: Option<u64>,
+}
+
+impl LockOwner {
+    fn read(path: &Path) -> Option<Self> {
+        let txt = fs::read_to_string(path).ok()?;
+        let mut parts = txt.split_whitespace();
+        let pid = parts.next()?.parse().ok()?;
+        let pending_until = match parts.next() {
+            Some(until) => Some(until.parse().ok()?),
+            None => None,
+        };
+        Some(Self { pid, pending_until })
+    }
+
+    /// Pending locks expire after their deadline; others live as long as the owner process.
+    fn is_live(&self) -> bool {
+        match self.pending_until {
+            Some(until) => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                now < until
+            }
+            None => self.pid == std::process::id() || process::is_alive(self.pid),
+        }
+    }
+}
+
+fn lock_path(install: &Arma3Install) -> Result<PathBuf> {
+    let cache = install
+        .context()
+        .cache_dir()
+        .ok_or_else(|| Arma3Error::Parse {
+            message: "could not determine the user cache directory".to_string(),
+        })?;
+    let game_dir = install.game_dir();
+    let game_dir = game_dir
+        .canonicalize()
+        .unwrap_or_else(|_| game_dir.to_path_buf());
+    Ok(cache
+        .join(LOCK_DIR_NAME)
+        .join(format!("instance-{:016x}.lock", fnv1a(&game_dir))))
+}
+
+/// FNV-1a hash of a path, for a stable per-install file name.
+fn fnv1a(path: &Path) -> u64 {
+    path.to_string_lossy()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::install::DetectionContext;
+
+    #[test]
+    fn own_and_pending_locks_are_held() {
+        let d = tempfile::tempdir().unwrap();
+        let game = d.path().join("Arma 3");
+        fs::create_dir_all(&game).unwrap();
+        fs::write(game.join("arma3.x86_64"), b"").unwrap();
+        let ctx = DetectionContext::isolated().env("XDG_CACHE_HOME", d.path().join("cache"));
+        let install = Arma3Install::with_context(&game, ctx).unwrap();
+
+        let lock = InstanceLock::acquire(&install).unwrap().unwrap();
+        let path = lock.path.clone();
+        assert_eq!(
+            InstanceLock::acquire(&install).unwrap().unwrap_err(),
+            std::process::id()
+        );
+
+        // A pending lock outlives its (exited) owner until the deadline.
+        lock.set_pending(u32::MAX, Duration::from_secs(60)).unwrap();
+        assert_eq!(
+            InstanceLock::acquire(&install).unwrap().unwrap_err(),
+            u32::MAX
+        );
+        fs::write(&path, format!("{} 1\n", u32::MAX)).unwrap();
+        InstanceLock::acquire(&install).unwrap().unwrap().release();
+        assert!(!path.exists());
+    }
+}
@@ -1,32 +1,54 @@
-use crate::error::Result;
+use crate::doctor::DoctorReport;
+use crate::error::{Arma3Error, Result};
 use crate::install::Arma3Install;
 use crate::launch::backend::{Backend, BackendParams};
+use crate::launch::instance::InstanceLock;
 use crate::launch::plan::CommandSpec;
+use crate::launch::steam_wait::SteamWait;
 use crate::mods::{LocalMod, ModSet};
 use crate::platform::path::arma_path_string;
 use std::ffi::{OsStr, OsString};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 mod backend;
+mod bundle;
 mod env;
+mod instance;
 mod plan;
+mod proton_options;
+mod session;
+mod steam_wait;
+
+const DEFAULT_STEAM_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_GAME_START_TIMEOUT: Duration = Duration::from_secs(120);
 
 pub use env::EnvVars;
+pub use instance::InstanceCheck;
+pub use proton_options::{DllOverride, ProtonOptions};
+pub use session::GameSession;
 
 /// How the game should be launched.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum LaunchMode {
-    /// Launch using Steam (Linux: `steam -applaunch ...` or Flatpak Steam; Windows: `steam.exe -applaunch ...`).
+    /// Launch using Steam (Linux: `steam -applaunch ...`, Flatpak or Snap Steam; Windows: `steam.exe -applaunch ...`).
     #[default]
     ThroughSteam,
     /// Launch the executable directly.
     Direct,
+    /// Open `steam://run/107410//<args>/` with the desktop URL handler (`xdg-open` on Linux).
+    ///
+    /// Works from sandboxes (e.g. a Flatpak frontend) that cannot run the Steam executable.
+    /// See [`Launcher::url_opener`].
+    SteamUrl,
 }
 
 /// Plan for launching (cfg path + spawnable command).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LaunchPlan {
     command: CommandSpec,
+    warnings: Vec<String>,
+    steam_wait: Option<SteamWait>,
 }
 
 impl LaunchPlan {
@@ -50,9 +72,21 @@ impl LaunchPlan {
         &self.command.env
     }
 
+    /// Non-fatal problems found while planning (e.g. Steam launch options that will be added).
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
     /// Spawn the described process.
+    ///
+    /// With [`Launcher::wait_for_steam`], this first starts Steam and waits for it, then blocks
+    /// until Steam's logs show the game starting (or the timeout passes), failing with
+    /// [`Arma3Error::SteamLaunchFailed`] if Steam logs a launch error.
     pub fn spawn(&self) -> Result<std::process::Child> {
-        self.command.spawn()
+        match &self.steam_wait {
+            Some(wait) => wait.spawn(&self.command),
+            None => self.command.spawn(),
+        }
     }
 }
 
@@ -61,7 +95,15 @@ impl LaunchPlan {
 pub struct Launcher {
     install: Arma3Install,
     launch_mode: LaunchMode,
-    disable_esync: bool,
+    proton: ProtonOptions,
+    compat_tool: Option<String>,
+    name_from_steam: bool,
+    par_file: Option<PathBuf>,
+    url_opener: Option<PathBuf>,
+    wait_for_steam: bool,
+    steam_timeout: Duration,
+    game_start_timeout: Duration,
+    instance_check: InstanceCheck,
     mods: ModSet,
     args: Vec<OsString>,
     env: EnvVars,
@@ -74,7 +116,15 @@ impl Launcher {
         Self {
             install,
             launch_mode: LaunchMode::default(),
-            disable_esync: false,
+            proton: ProtonOptions::default(),
+            compat_tool: None,
+            name_from_steam: false,
+            par_file: None,
+            url_opener: None,
+            wait_for_steam: false,
+            steam_timeout: DEFAULT_STEAM_TIMEOUT,
+            game_start_timeout: DEFAULT_GAME_START_TIMEOUT,
+            instance_check: InstanceCheck::default(),
             mods: ModSet::new(),
             args: Vec::new(),
             env: EnvVars::default(),
@@ -88,10 +138,87 @@ impl Launcher {
         self
     }
 
-    /// If true (Linux/Proton direct only), set `PROTON_NO_ESYNC=1` similarly to common launchers.
-    /// On Windows and on ThroughSteam, this is effectively a no-op.
+    /// If true, set `PROTON_NO_ESYNC=1` (Linux Proton direct launches only).
+    ///
+    /// Shorthand for [`ProtonOptions::no_esync`] on the current [`Launcher::proton_options`],
+    /// so planning fails the same way when the launch mode cannot apply it.
     pub fn disable_esync(mut self, value: bool) -> Self {
-        self.disable_esync = value;
+        self.proton = self.proton.no_esync(value);
+        self
+    }
+
+    /// Set Proton/Wine/DXVK options (Linux Proton direct launches only).
+    ///
+    /// Planning fails with [`Arma3Error::UnsupportedOption`] if any option is set and the
+    /// launch is not a Proton direct launch.
+    pub fn proton_options(mut self, options: ProtonOptions) -> Self {
+        self.proton = options;
+        self
+    }
+
+    /// Use a specific compatibility tool for Proton direct launches instead of Steam's mapping.
+    ///
+    /// `name` is the tool's internal or display name, as listed by
+    /// [`detect_compat_tools`](crate::detect_compat_tools).
+    pub fn compat_tool(mut self, name: impl Into<String>) -> Self {
+        self.compat_tool = Some(name.into());
+        self
+    }
+
+    /// If true, pass `-name=<persona name>` of the account most recently signed in to the Steam
+    /// installation that owns the install, unless `-name=` is set.
+    ///
+    /// Arma 3 uses `-name` to pick (or create) the player profile.
+    pub fn name_from_steam(mut self, value: bool) -> Self {
+        self.name_from_steam = value;
+        self
+    }
+
+    /// Pass a parameter file (`-par=<file>`), translated for Proton installs.
+    pub fn par_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.par_file = Some(path.into());
+        self
+    }
+
+    /// Program used to open the URL in [`LaunchMode::SteamUrl`] (default: `xdg-open` on Linux,
+    /// `explorer` on Windows). It is called with the URL as its only argument.
+    pub fn url_opener(mut self, program: impl Into<PathBuf>) -> Self {
+        self.url_opener = Some(program.into());
+        self
+    }
+
+    /// If true ([`LaunchMode::ThroughSteam`] only), start Steam when it is not running and wait
+    /// until it is ready before `-applaunch`, then watch Steam's logs for launch failures.
+    ///
+    /// Without this, Steam may drop the arguments when `-applaunch` has to start it.
+    pub fn wait_for_steam(mut self, value: bool) -> Self {
+        self.wait_for_steam = value;
+        self
+    }
+
+    /// How long [`Launcher::wait_for_steam`] waits for Steam, and then for the game to start
+    /// (default: 60 seconds).
+    pub fn steam_timeout(mut self, timeout: Duration) -> Self {
+        self.steam_timeout = timeout;
+        self
+    }
+
+    /// How long [`Launcher::start`] waits for the game process to appear (default: 120 seconds).
+    ///
+    /// [`Launcher::launch`] through Steam holds the instance lock for this long.
+    pub fn game_start_timeout(mut self, timeout: Duration) -> Self {
+        self.game_start_timeout = timeout;
+        self
+    }
+
+    /// Check for an Arma 3 client or server already running from this install before launching.
+    ///
+    /// Running games are found by scanning processes; a lock file in the user's cache
+    /// directory (taken from the install's [`DetectionContext`](crate::DetectionContext)) also
+    /// covers instances that are still starting. For launches through Steam the lock is held
+    /// for [`Launcher::game_start_timeout`] after spawning.
+    pub fn instance_check(mut self, check: InstanceCheck) -> Self {
+        self.instance_check = check;
         self
     }
 
@@ -163,39 +290,291 @@ impl Launcher {
         &self.install
     }
 
+    /// Check the environment for this launcher's install, mods and compatibility tool.
+    ///
+    /// See [`doctor`](crate::doctor).
+    pub fn doctor(&self) -> DoctorReport {
+        crate::doctor::run(
+            self.install.steam_installation().as_ref(),
+            Some(&self.install),
+            self.mods.as_slice(),
+            self.compat_tool.as_deref(),
+        )
+    }
+
     /// Build the plan that would be executed.
     pub fn plan(&self) -> Result<LaunchPlan> {
-        let user_args = self.args_with_mods();
+        let uses_proton = self.launch_mode == LaunchMode::Direct && self.install.is_proton();
+        if !uses_proton {
+            let reason = match self.launch_mode {
+                LaunchMode::ThroughSteam | LaunchMode::SteamUrl => {
+                    "Steam starts the game itself; configure this in Steam instead"
+                }
+                LaunchMode::Direct => "the install does not run through Proton",
+            };
+            self.reject_proton_settings(reason)?;
+        }
+
+        if self.wait_for_steam && self.launch_mode != LaunchMode::ThroughSteam {
+            return Err(Arma3Error::UnsupportedOption {
+                message: "waiting for Steam requires LaunchMode::ThroughSteam".to_string(),
+            });
+        }
+
+        match self.launch_mode {
+            LaunchMode::ThroughSteam => {
+                let mut plan = self.plan_with(&backend::steam::SteamBackend)?;
+                plan.warnings.extend(self.snap_warnings());
+                if self.wait_for_steam {
+                    plan.steam_wait = Some(SteamWait {
+                        startup: backend::steam::startup_command(&self.install),
+                        steam: self
+                            .install
+                            .steam_installation()
+                            .ok_or(Arma3Error::SteamNotFound)?,
+                        context: self.install.context().clone(),
+                        timeout: self.steam_timeout,
+                    });
+                }
+                Ok(plan)
+            }
+            LaunchMode::SteamUrl => {
+                let mut plan = self.plan_with(&backend::steam_url::SteamUrlBackend)?;
+                plan.warnings.extend(self.snap_warnings());
+                Ok(plan)
+            }
+            LaunchMode::Direct => {
+                if uses_proton {
+                    self.plan_with(&backend::proton::ProtonBackend)
+                } else {
+                    self.plan_with(&backend::direct::DirectBackend)
+                }
+            }
+        }
+    }
+
+    /// Build a plan that runs Steam's own launch command with our arguments added.
+    ///
+    /// `command` is what Steam substitutes for `%command%` in the game's launch options (the
+    /// full Proton/runtime command line on Linux). Mods and arguments are inserted right after
+    /// the game executable; the launch mode is ignored. Proton options are applied through the
+    /// environment when the command runs Proton; a compatibility tool cannot be changed here.
+    pub fn wrap_steam_command<I, S>(&self, command: I) -> Result<LaunchPlan>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let command: Vec<OsString> = command
+            .into_iter()
+            .map(|s| s.as_ref().to_os_string())
+            .collect();
+        if backend::wrap::runs_proton(&command) {
+            if let Some(tool) = &self.compat_tool {
+                return Err(Arma3Error::UnsupportedOption {
+                    message: format!(
+                        "compatibility tool '{tool}' cannot be applied: Steam already chose the tool; select it in Steam instead"
+                    ),
+                });
+            }
+        } else {
+            self.reject_proton_settings("Steam's command line does not run Proton")?;
+        }
+
+        self.plan_with(&backend::wrap::WrapBackend { command: &command })
+    }
+
+    /// Spawn the game process and return the `Child`.
+    pub fn launch(&self) -> Result<std::process::Child> {
+        let lock = self.lock_instance()?;
+        let child = self.plan().and_then(|plan| plan.spawn());
+        if let Some(lock) = lock {
+            let recorded = match &child {
+                // The spawned process is the game itself, or Proton, which exits with it.
+                Ok(child) if self.launch_mode == LaunchMode::Direct => lock.set_owner(child.id()),
+                // Steam starts the game later; hold the lock until it can be found.
+                Ok(child) => lock.set_pending(child.id(), self.game_start_timeout),
+                Err(_) => Ok(()),
+            };
+            // The game is already running: drop a lock that cannot be updated rather than
+            // losing the child.
+            if child.is_err() || recorded.is_err() {
+                lock.release();
+            }
+        }
+        child
+    }
+
+    /// Launch the game and return a [`GameSession`] tracking the game process itself.
+    ///
+    /// Unlike [`Launcher::launch`], whose `Child` may be Steam or Proton, this waits until the
+    /// Arma 3 process is running (see [`Launcher::game_start_timeout`]).
+    pub fn start(&self) -> Result<GameSession> {
+        let lock = match self.lock_instance() {
+            Err(Arma3Error::AlreadyRunning { pid })
+                if self.instance_check == InstanceCheck::Attach =>
+            {
+                return GameSession::attach(&self.install)
+                    .ok_or(Arma3Error::AlreadyRunning { pid });
+            }
+            other => other?,
+        };
+
+        let session = self.spawn_session();
+        match (lock, session) {
+            (Some(lock), Ok(session)) => Ok(session.with_lock(lock)),
+            (Some(lock), Err(e)) => {
+                lock.release();
+                Err(e)
+            }
+            (None, session) => session,
+        }
+    }
+
+    fn spawn_session(&self) -> Result<GameSession> {
+        let plan = self.plan()?;
+        let child = plan.spawn()?;
+        match self.launch_mode {
+            LaunchMode::Direct if !self.install.is_proton() => {
+                let pid = child.id();
+                Ok(GameSession::from_child(&self.install, child, pid))
+            }
+            // Proton exits with the game's status.
+            LaunchMode::Direct => GameSession::find(
+                &self.install,
+                child,
+                true,
+                self.game_start_timeout,
+                plan.env(),
+            ),
+            LaunchMode::ThroughSteam | LaunchMode::SteamUrl => GameSession::find(
+                &self.install,
+                child,
+                false,
+                self.game_start_timeout,
+                plan.env(),
+            ),
+        }
+    }
+
+    /// Apply [`Launcher::instance_check`]: take the instance lock, or fail if already running.
+    fn lock_instance(&self) -> Result<Option<InstanceLock>> {
+        if self.instance_check == InstanceCheck::Off {
+            return Ok(None);
+        }
+        match InstanceLock::acquire(&self.install)? {
+            Ok(lock) => Ok(Some(lock)),
+            Err(pid) => Err(Arma3Error::AlreadyRunning { pid }),
+        }
+    }
+
+    fn plan_with(&self, backend: &dyn Backend) -> Result<LaunchPlan> {
+        let mut warnings = Vec::new();
+        let mut user_args = self.args_with_mods();
+        if self.name_from_steam {
+            self.push_steam_name(&mut user_args, &mut warnings);
+        }
+
+        let host_paths: Vec<PathBuf> = self
+            .mods
+            .iter()
+            .map(|m| m.path().to_path_buf())
+            .chain(self.par_file.clone())
+            .collect();
 
         let params = BackendParams {
             install: &self.install,
             user_args: &user_args,
             user_env: &self.env,
             working_dir: self.working_dir.as_deref(),
-            disable_esync: self.disable_esync,
+            host_paths: &host_paths,
+            proton: &self.proton,
+            compat_tool: self.compat_tool.as_deref(),
+            url_opener: self.url_opener.as_deref(),
         };
 
-        let command = match self.launch_mode {
-            LaunchMode::ThroughSteam => backend::steam::SteamBackend.plan(&params)?,
-            LaunchMode::Direct => {
-                if self.install.is_proton() {
-                    backend::proton::ProtonBackend.plan(&params)?
-                } else {
-                    backend::direct::DirectBackend.plan(&params)?
-                }
+        let command = backend.plan(&params)?;
+        warnings.extend(backend.warnings(&params));
+
+        Ok(LaunchPlan {
+            command,
+            warnings,
+            steam_wait: None,
+        })
+    }
+
+    /// Mods and the `-par` file a game started by Snap Steam cannot read.
+    fn snap_warnings(&self) -> Vec<String> {
+        #[cfg(target_os = "linux")]
+        {
+            use crate::steam::{snap, SteamPackage};
+
+            let steam = self.install.steam_installation();
+            if steam.is_some_and(|s| s.package() == SteamPackage::Snap) {
+                let paths = self
+                    .mods
+                    .iter()
+                    .map(|m| m.path())
+                    .chain(self.par_file.as_deref());
+                return paths
+                    .filter_map(|p| {
+                        let problem = snap::snap_access_problem(self.install.context(), p)?;
+                        Some(format!("{} may not be readable: {problem}", p.display()))
+                    })
+                    .collect();
             }
-        };
+        }
+        Vec::new()
+    }
 
-        Ok(LaunchPlan { command })
+    /// Reject Proton options and the compatibility tool override when they cannot be applied.
+    fn reject_proton_settings(&self, reason: &str) -> Result<()> {
+        if !self.proton.is_default() {
+            let names = self.proton.configured_names().join(", ");
+            return Err(Arma3Error::UnsupportedOption {
+                message: format!("Proton options ({names}) cannot be applied: {reason}"),
+            });
+        }
+        if let Some(tool) = &self.compat_tool {
+            return Err(Arma3Error::UnsupportedOption {
+                message: format!("compatibility tool '{tool}' cannot be applied: {reason}"),
+            });
+        }
+        Ok(())
     }
 
-    /// Spawn the game process and return the `Child`.
-    pub fn launch(&self) -> Result<std::process::Child> {
-        self.plan()?.spawn()
+    fn push_steam_name(&self, args: &mut Vec<OsString>, warnings: &mut Vec<String>) {
+        if args
+            .iter()
+            .any(|a| a.to_string_lossy().starts_with("-name="))
+        {
+            return;
+        }
+        let account = self.install.steam_installation().and_then(|steam| {
+            crate::steam::users::active_account(steam.root())
+                .ok()
+                .flatten()
+        });
+        match account {
+            Some(account) if !account.persona_name().is_empty() => {
+                args.push(OsString::from(format!("-name={}", account.persona_name())));
+            }
+            _ => {
+                warnings.push("could not determine the active Steam account for -name".to_string())
+            }
+        }
     }
 
     fn args_with_mods(&self) -> Vec<OsString> {
         let mut args = self.args.clone();
+        if let Some(par) = &self.par_file {
+            if !args
+                .iter()
+                .any(|a| a.to_string_lossy().starts_with("-par="))
+            {
+                let par = arma_path_string(par, self.install.is_proton());
+                args.push(OsString::from(format!("-par={par}")));
+            }
+        }
         if self.mods.is_empty() {
             return args;
         }
@@ -0,0 +1,224 @@
+use crate::steam::{
+    ENV_DXVK_ASYNC, ENV_DXVK_HUD, ENV_PROTON_ENABLE_NVAPI, ENV_PROTON_FORCE_LARGE_ADDRESS_AWARE,
+    ENV_PROTON_LOG, ENV_PROTON_LOG_DIR, ENV_PROTON_NO_ESYNC, ENV_PROTON_NO_FSYNC,
+    ENV_PROTON_USE_WINED3D, ENV_WINEDLLOVERRIDES,
+};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Load order for a single `WINEDLLOVERRIDES` entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DllOverride {
+    /// `n`: native DLL only.
+    Native,
+    /// `b`: builtin (Wine) DLL only.
+    Builtin,
+    /// `n,b`: native first, then builtin.
+    NativeThenBuiltin,
+    /// `b,n`: builtin first, then native.
+    BuiltinThenNative,
+    /// Empty value: disable the DLL.
+    Disabled,
+}
+
+impl DllOverride {
+    fn as_str(self) -> &'static str {
+        match self {
+            DllOverride::Native => "n",
+            DllOverride::Builtin => "b",
+            DllOverride::NativeThenBuiltin => "n,b",
+            DllOverride::BuiltinThenNative => "b,n",
+            DllOverride::Disabled => "",
+        }
+    }
+}
+
+/// Proton/Wine/DXVK settings applied to Proton direct launches.
+///
+/// These map to the environment variables Proton reads at startup. They can only be honoured
+/// when this library spawns Proton itself (`LaunchMode::Direct` on a Linux Proton install);
+/// other launch modes reject non-default options when planning.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProtonOptions {
+    no_esync: bool,
+    no_fsync: bool,
+    use_wined3d: bool,
+    enable_nvapi: bool,
+    log: bool,
+    log_dir: Option<PathBuf>,
+    force_large_address_aware: bool,
+    dxvk_async: bool,
+    dxvk_hud: Option<String>,
+    dll_overrides: Vec<(String, DllOverride)>,
+}
+
+impl ProtonOptions {
+    /// Create options with every setting left at Proton's default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Disable eventfd-based synchronization (`PROTON_NO_ESYNC=1`).
+    pub fn no_esync(mut self, value: bool) -> Self {
+        self.no_esync = value;
+        self
+    }
+
+    /// Disable futex-based synchronization (`PROTON_NO_FSYNC=1`).
+    pub fn no_fsync(mut self, value: bool) -> Self {
+        self.no_fsync = value;
+        self
+    }
+
+    /// Use WineD3D instead of DXVK (`PROTON_USE_WINED3D=1`).
+    pub fn use_wined3d(mut self, value: bool) -> Self {
+        self.use_wined3d = value;
+        self
+    }
+
+    /// Enable NVAPI/DLSS support (`PROTON_ENABLE_NVAPI=1`).
+    pub fn enable_nvapi(mut self, value: bool) -> Self {
+        self.enable_nvapi = value;
+        self
+    }
+
+    /// Write a Proton log (`PROTON_LOG=1`), by default to `$HOME/steam-<appid>.log`.
+    ///
+    /// `false` also clears a directory set with [`ProtonOptions::log_dir`].
+    pub fn log(mut self, value: bool) -> Self {
+        self.log = value;
+        if !value {
+            self.log_dir = None;
+        }
+        self
+    }
+
+    /// Write the Proton log into `dir` (`PROTON_LOG_DIR`). Implies [`ProtonOptions::log`].
+    pub fn log_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.log = true;
+        self.log_dir = Some(dir.into());
+        self
+    }
+
+    /// Force the large-address-aware flag on 32-bit executables (`PROTON_FORCE_LARGE_ADDRESS_AWARE=1`).
+    pub fn force_large_address_aware(mut self, value: bool) -> Self {
+        self.force_large_address_aware = value;
+        self
+    }
+
+    /// Enable asynchronous pipeline compilation in DXVK forks that support it (`DXVK_ASYNC=1`).
+    pub fn dxvk_async(mut self, value: bool) -> Self {
+        self.dxvk_async = value;
+        self
+    }
+
+    /// Show the DXVK HUD with the given spec, e.g. `fps,memory` (`DXVK_HUD`).
+    pub fn dxvk_hud(mut self, spec: impl Into<String>) -> Self {
+        self.dxvk_hud = Some(spec.into());
+        self
+    }
+
+    /// Add a `WINEDLLOVERRIDES` entry for `dll` (e.g. `dinput8`).
+    ///
+    /// Entries are appended after any `WINEDLLOVERRIDES` already present in the launcher or
+    /// process environment.
+    pub fn dll_override(mut self, dll: impl Into<String>, mode: DllOverride) -> Self {
+        self.dll_overrides.push((dll.into(), mode));
+        self
+    }
+
+    /// True if no setting differs from Proton's default.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Proton log directory, if set.
+    pub fn log_directory(&self) -> Option<&Path> {
+        self.log_dir.as_deref()
+    }
+
+    /// Names of the environment variables these options would set.
+    pub(crate) fn configured_names(&self) -> Vec<&'static str> {
+        self.flags()
+            .into_iter()
+            .filter(|(_, on)| *on)
+            .map(|(name, _)| name)
+            .chain(self.log_dir.as_ref().map(|_| ENV_PROTON_LOG_DIR))
+            .chain(self.dxvk_hud.as_ref().map(|_| ENV_DXVK_HUD))
+            .chain((!self.dll_overrides.is_empty()).then_some(ENV_WINEDLLOVERRIDES))
+            .collect()
+    }
+
+    /// Environment for the Proton process.
+    ///
+    /// `existing_dll_overrides` is the `WINEDLLOVERRIDES` value the process would otherwise
+    /// receive; our entries are appended to it.
+    pub(crate) fn env(
+        &self,
+        existing_dll_overrides: Option<OsString>,
+    ) -> Vec<(OsString, OsString)> {
+        let mut env: Vec<(OsString, OsString)> = self
+            .flags()
+            .into_iter()
+            .filter(|(_, on)| *on)
+            .map(|(name, _)| (OsString::from(name), OsString::from("1")))
+            .collect();
+
+        if let Some(dir) = &self.log_dir {
+            env.push((
+                OsString::from(ENV_PROTON_LOG_DIR),
+                dir.as_os_str().to_os_string(),
+            ));
+        }
+        if let Some(hud) = &self.dxvk_hud {
+            env.push((OsString::from(ENV_DXVK_HUD), OsString::from(hud)));
+        }
+
+        if !self.dll_overrides.is_empty() {
+            let mut value = existing_dll_overrides.unwrap_or_default();
+            for (dll, mode) in &self.dll_overrides {
+                if !value.is_empty() {
+                    value.push(";");
+                }
+                value.push(format!("{dll}={}", mode.as_str()));
+            }
+            env.push((OsString::from(ENV_WINEDLLOVERRIDES), value));
+        }
+
+        env
+    }
+
+    fn flags(&self) -> [(&'static str, bool); 7] {
+        [
+            (ENV_PROTON_NO_ESYNC, self.no_esync),
+            (ENV_PROTON_NO_FSYNC, self.no_fsync),
+            (ENV_PROTON_USE_WINED3D, self.use_wined3d),
+            (ENV_PROTON_ENABLE_NVAPI, self.enable_nvapi),
+            (ENV_PROTON_LOG, self.log),
+            (
+                ENV_PROTON_FORCE_LARGE_ADDRESS_AWARE,
+                self.force_large_address_aware,
+            ),
+            (ENV_DXVK_ASYNC, self.dxvk_async),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabling_log_drops_log_dir() {
+        let opts = ProtonOptions::default().log_dir("/tmp/logs");
+        assert_eq!(
+            opts.configured_names(),
+            [ENV_PROTON_LOG, ENV_PROTON_LOG_DIR]
+        );
+
+        let opts = opts.log(false);
+        assert_eq!(opts.log_directory(), None);
+        assert!(opts.env(None).is_empty());
+        assert!(opts.is_default());
+    }
+}
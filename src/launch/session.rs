@@ -0,0 +1,326 @@
+use crate::error::{Arma3Error, Result};
+use crate::install::Arma3Install;
+use crate::launch::instance::InstanceLock;
+use crate::platform::process;
+use crate::rpt::{self, CrashReport, RptTail};
+use crate::steam::{ENV_STEAM_COMPAT_DATA_PATH, ENV_STEAM_COMPAT_TOOL_PATHS};
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process::{Child, ExitStatus};
+use std::time::{Duration, Instant, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const DEFAULT_TERMINATE_TIMEOUT: Duration = Duration::from_secs(10);
+const WINESERVER_RELS: [&str; 2] = ["files/bin/wineserver", "dist/bin/wineserver"];
+
+/// A running Arma 3 game process.
+///
+/// For Steam launches the process that was spawned is the Steam client, so the game is found
+/// by scanning running processes instead. Its exit status is only known when this library
+/// spawned the game (or Proton, which exits with the game's status) itself.
+#[derive(Debug)]
+pub struct GameSession {
+    pid: u32,
+    child: Option<Child>,
+    child_reports_exit: bool,
+    wine: Option<WineContext>,
+    lock: Option<InstanceLock>,
+    rpt_dir: Option<PathBuf>,
+    started_at: SystemTime,
+    exit_status: Option<ExitStatus>,
+    terminated: bool,
+}
+
+/// Proton prefix and matching `wineserver`, to shut down Wine after the game.
+#[derive(Debug, Clone)]
+struct WineContext {
+    prefix: PathBuf,
+    wineserver: PathBuf,
+}
+
+impl GameSession {
+    /// Attach to an already running game for `install`, if any.
+    pub fn attach(install: &Arma3Install) -> Option<Self> {
+        let pid = find_game_pid(install)?;
+        Some(Self {
+            pid,
+            child: None,
+            child_reports_exit: false,
+            wine: wine_context(process::environ(pid)),
+            lock: None,
+            rpt_dir: install.rpt_dir().ok(),
+            // The start time is unknown: any RPT written so far may be this session's.
+            started_at: SystemTime::UNIX_EPOCH,
+            exit_status: None,
+            terminated: false,
+        })
+    }
+
+    /// Track a spawned process that is the game itself, or a wrapper that exits with the
+    /// game's status (Proton).
+    pub(crate) fn from_child(install: &Arma3Install, child: Child, pid: u32) -> Self {
+        Self {
+            pid,
+            child: Some(child),
+            child_reports_exit: true,
+            wine: None,
+            lock: None,
+            rpt_dir: install.rpt_dir().ok(),
+            started_at: SystemTime::now(),
+            exit_status: None,
+            terminated: false,
+        }
+    }
+
+    /// Wait up to `timeout` for the game process started by `child` to appear.
+    ///
+    /// `env` is the environment `child` was given, used when the game's own is unreadable.
+    pub(crate) fn find(
+        install: &Arma3Install,
+        mut child: Child,
+        child_reports_exit: bool,
+        timeout: Duration,
+        env: &[(OsString, OsString)],
+    ) -> Result<Self> {
+        let started_at = SystemTime::now();
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(pid) = find_game_pid(install) {
+                let wine = wine_context(process::environ(pid))
+                    .or_else(|| wine_context(env.iter().cloned()));
+                return Ok(Self {
+                    pid,
+                    child: Some(child),
+                    child_reports_exit,
+                    wine,
+                    lock: None,
+                    rpt_dir: install.rpt_dir().ok(),
+                    started_at,
+                    exit_status: None,
+                    terminated: false,
+                });
+            }
+            if Instant::now() >= deadline {
+                let _ = child.try_wait();
+                return Err(Arma3Error::Spawn {
+                    message: format!("game process did not appear within {}s", timeout.as_secs()),
+                });
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Hand over the instance lock; it is pointed at the game and removed once it exits.
+    ///
+    /// If the lock file cannot be updated the lock is released instead; the game is running
+    /// either way.
+    pub(crate) fn with_lock(mut self, lock: InstanceLock) -> Self {
+        match lock.set_owner(self.pid) {
+            Ok(()) => self.lock = Some(lock),
+            Err(_) => lock.release(),
+        }
+        self
+    }
+
+    /// Game process ID.
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// True while the game process is running.
+    pub fn is_running(&self) -> bool {
+        process::is_alive(self.pid)
+    }
+
+    /// RPT log written by this session, once the game has created it.
+    ///
+    /// For attached sessions this is the newest RPT in the install's log directory.
+    pub fn rpt_path(&self) -> Option<PathBuf> {
+        rpt::newest_rpt_since(self.rpt_dir.as_deref()?, self.started_at)
+    }
+
+    /// Wait up to `timeout` for the game to create its RPT, then follow it from the start.
+    ///
+    /// Returns `None` if no RPT appeared in time or the game exited first.
+    pub fn wait_for_rpt(&self, timeout: Duration) -> Result<Option<RptTail>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(path) = self.rpt_path() {
+                return Ok(Some(RptTail::open(path)));
+            }
+            if Instant::now() >= deadline || !self.is_running() {
+                return Ok(None);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Block until the game exits.
+    ///
+    /// Returns the exit status when it is available (direct launches), `None` otherwise.
+    pub fn wait(&mut self) -> Result<Option<ExitStatus>> {
+        if self.child_reports_exit {
+            if let Some(child) = &mut self.child {
+                let status = child.wait()?;
+                self.exit_status = Some(status);
+                self.release_lock();
+                return Ok(Some(status));
+            }
+        }
+
+        while process::is_alive(self.pid) {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        // Reap the Steam client process if it has exited meanwhile.
+        if let Some(child) = &mut self.child {
+            let _ = child.try_wait();
+        }
+        self.release_lock();
+        Ok(None)
+    }
+
+    /// Stop the game and everything it started, waiting up to 10 seconds before killing.
+    ///
+    /// See [`GameSession::terminate_with_timeout`].
+    pub fn terminate(&mut self) -> Result<()> {
+        self.terminate_with_timeout(DEFAULT_TERMINATE_TIMEOUT)
+    }
+
+    /// Stop the game and everything it started.
+    ///
+    /// The game and, for direct launches, the spawned process (Proton, the Steam runtime
+    /// wrapper, ...) are signalled together with their child processes; whatever is still running after `timeout` is
+    /// killed. For Proton launches, `wineserver -k` then stops the remaining Wine processes in
+    /// the game's prefix.
+    pub fn terminate_with_timeout(&mut self, timeout: Duration) -> Result<()> {
+        let mut roots = vec![self.pid];
+        // A Steam child may be the Steam client itself, which must keep running.
+        if let Some(child) = self.child.as_mut().filter(|_| self.child_reports_exit) {
+            if child.try_wait()?.is_none() {
+                roots.push(child.id());
+            }
+        }
+        let tree: Vec<u32> = process::process_tree(&roots)
+            .into_iter()
+            .filter(|pid| process::is_alive(*pid))
+            .collect();
+        process::signal(&tree, false)?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining: Vec<u32> = tree
+                .iter()
+                .copied()
+                .filter(|pid| process::is_alive(*pid))
+                .collect();
+            if remaining.is_empty() {
+                break;
+            }
+            if Instant::now() >= deadline {
+                process::signal(&remaining, true)?;
+                break;
+            }
+            std::thread::sleep(POLL_INTERVAL.min(timeout));
+        }
+
+        if let Some(wine) = &self.wine {
+            std::process::Command::new(&wine.wineserver)
+                .arg("-k")
+                .env("WINEPREFIX", &wine.prefix)
+                .status()
+                .map_err(|e| Arma3Error::Spawn {
+                    message: format!("failed to run {}: {e}", wine.wineserver.display()),
+                })?;
+        }
+
+        if let Some(child) = &mut self.child {
+            if self.child_reports_exit {
+                child.wait()?;
+            } else {
+                child.try_wait()?;
+            }
+        }
+        self.terminated = true;
+        self.release_lock();
+        Ok(())
+    }
+
+    /// Check whether the game crashed, once it has exited.
+    ///
+    /// A crash is an unsuccessful exit status (direct launches) or new `.mdmp`/`.bidmp`
+    /// dumps next to the RPT. Returns `None` while the game runs, after a clean exit, and
+    /// after [`GameSession::terminate`].
+    pub fn crash_report(&mut self) -> Result<Option<CrashReport>> {
+        if self.terminated || self.is_running() {
+            return Ok(None);
+        }
+        if self.exit_status.is_none() && self.child_reports_exit {
+            if let Some(child) = &mut self.child {
+                self.exit_status = child.try_wait()?;
+            }
+        }
+        Ok(CrashReport::detect(
+            self.rpt_dir.as_deref(),
+            self.started_at,
+            self.exit_status,
+        ))
+    }
+
+    fn release_lock(&mut self) {
+        if let Some(lock) = self.lock.take() {
+            lock.release();
+        }
+    }
+}
+
+/// Wine prefix and `wineserver` from a Proton process environment.
+fn wine_context(env: impl IntoIterator<Item = (OsString, OsString)>) -> Option<WineContext> {
+    let mut compat_data = None;
+    let mut tool_paths = None;
+    for (key, value) in env {
+        if key == ENV_STEAM_COMPAT_DATA_PATH {
+            compat_data = Some(PathBuf::from(value));
+        } else if key == ENV_STEAM_COMPAT_TOOL_PATHS {
+            tool_paths = Some(value);
+        }
+    }
+
+    let prefix = compat_data?.join("pfx");
+    let wineserver = std::env::split_paths(&tool_paths?)
+        .flat_map(|dir| WINESERVER_RELS.map(|rel| dir.join(rel)))
+        .find(|p| p.is_file())?;
+    Some(WineContext { prefix, wineserver })
+}
+
+fn find_game_pid(install: &Arma3Install) -> Option<u32> {
+    let exe_name = install.executable().file_name()?.to_str()?;
+    process::find_game_pid(exe_name, install.game_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn finds_wineserver_for_proton_prefix() {
+        let d = tempfile::tempdir().unwrap();
+        let proton = d.path().join("Proton 9.0");
+        let runtime = d.path().join("SteamLinuxRuntime_sniper");
+        fs::create_dir_all(proton.join("files/bin")).unwrap();
+        fs::write(proton.join("files/bin/wineserver"), "").unwrap();
+
+        let tool_paths = std::env::join_paths([&proton, &runtime]).unwrap();
+        let env = vec![
+            (
+                OsString::from("STEAM_COMPAT_DATA_PATH"),
+                OsString::from("/c/107410"),
+            ),
+            (OsString::from("STEAM_COMPAT_TOOL_PATHS"), tool_paths),
+        ];
+        let wine = wine_context(env).unwrap();
+        assert_eq!(wine.prefix, PathBuf::from("/c/107410/pfx"));
+        assert_eq!(wine.wineserver, proton.join("files/bin/wineserver"));
+        assert!(wine_context(Vec::new()).is_none());
+    }
+}
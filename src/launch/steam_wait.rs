@@ -0,0 +1,171 @@
+use crate::error::{Arma3Error, Result};
+use crate::install::DetectionContext;
+use crate::launch::plan::CommandSpec;
+use crate::platform::tail::FileTail;
+use crate::steam::{process, SteamInstallation, ARMA3_APP_ID_STR};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const STEAM_LOG_FILES: [&str; 2] = ["logs/console_log.txt", "logs/content_log.txt"];
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Start Steam if needed, wait until it accepts `-applaunch`, and watch its logs afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SteamWait {
+    pub(crate) startup: CommandSpec,
+    pub(crate) steam: SteamInstallation,
+    pub(crate) context: DetectionContext,
+    pub(crate) timeout: Duration,
+}
+
+impl SteamWait {
+    pub(crate) fn spawn(&self, command: &CommandSpec) -> Result<std::process::Child> {
+        let deadline = Instant::now() + self.timeout;
+        if !process::is_steam_ready(&self.context, &self.steam) {
+            if !process::is_steam_running(&self.context) {
+                self.startup.spawn()?;
+            }
+            while !process::is_steam_ready(&self.context, &self.steam) {
+                if Instant::now() >= deadline {
+                    return Err(Arma3Error::SteamLaunchFailed {
+                        message: format!(
+                            "Steam did not become ready within {}s",
+                            self.timeout.as_secs()
+                        ),
+                    });
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+
+        let mut watch = LogWatch::new(self.steam.root());
+        let child = command.spawn()?;
+        watch.wait(ARMA3_APP_ID_STR, deadline)?;
+        Ok(child)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum LaunchEvent {
+    Started,
+    Failed(String),
+}
+
+/// Classify a Steam log line about `app_id`'s launch.
+fn classify(line: &str, app_id: &str) -> Option<LaunchEvent> {
+    let tag = format!("AppID {app_id}");
+    let mentions_app = line
+        .match_indices(&tag)
+        .any(|(i, _)| !line[i + tag.len()..].starts_with(|c: char| c.is_ascii_digit()));
+    if !mentions_app {
+        return None;
+    }
+    if line.contains("Game process added") || line.contains("App Running") {
+        return Some(LaunchEvent::Started);
+    }
+    let lower = line.to_ascii_lowercase();
+    if ["failed", "error", "canceled", "cancelled"]
+        .iter()
+        .any(|w| lower.contains(w))
+    {
+        // Drop the `[date time] ` prefix Steam writes before each line.
+        let message = match line.strip_prefix('[') {
+            Some(rest) => rest.split_once("] ").map_or(line, |(_, m)| m),
+            None => line,
+        };
+        return Some(LaunchEvent::Failed(message.trim().to_string()));
+    }
+    None
+}
+
+/// Follows lines appended to Steam's logs after it was created.
+struct LogWatch {
+    files: Vec<FileTail>,
+}
+
+impl LogWatch {
+    fn new(steam_root: &Path) -> Self {
+        let files = STEAM_LOG_FILES
+            .iter()
+            .map(|rel| FileTail::from_end(steam_root.join(rel)))
+            .collect();
+        Self { files }
+    }
+
+    /// Wait until the game starts, a failure is logged, or `deadline` passes (not an error:
+    /// Steam may log nothing, e.g. when a launch prompt is shown).
+    fn wait(&mut self, app_id: &str, deadline: Instant) -> Result<()> {
+        loop {
+            let lines = self
+                .files
+                .iter_mut()
+                .flat_map(|f| f.read_lines().unwrap_or_default());
+            for line in lines.collect::<Vec<_>>() {
+                match classify(&line, app_id) {
+                    Some(LaunchEvent::Started) => return Ok(()),
+                    Some(LaunchEvent::Failed(message)) => {
+                        return Err(Arma3Error::SteamLaunchFailed { message })
+                    }
+                    None => {}
+                }
+            }
+            if Instant::now() >= deadline {
+                return Ok(());
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn classifies_steam_log_lines() {
+        assert_eq!(
+            classify(
+                "[2024-05-01 20:00:01] Game process added : AppID 107410 \"...\", ProcID 4242",
+                "107410"
+            ),
+            Some(LaunchEvent::Started)
+        );
+        assert_eq!(
+            classify(
+                "[2024-05-01 20:00:01] AppID 107410 update canceled : Missing executable (Suspended,)",
+                "107410"
+            ),
+            Some(LaunchEvent::Failed(
+                "AppID 107410 update canceled : Missing executable (Suspended,)".to_string()
+            ))
+        );
+        assert_eq!(
+            classify("[2024-05-01 20:00:01] AppID 228980 update failed", "107410"),
+            None
+        );
+        assert_eq!(classify("AppID 1074100 update failed", "107410"), None);
+    }
+
+    #[test]
+    fn reports_failures_logged_after_launch() {
+        let d = tempfile::tempdir().unwrap();
+        fs::create_dir_all(d.path().join("logs")).unwrap();
+        let console = d.path().join("logs/console_log.txt");
+        fs::write(
+            &console,
+            "[2024-05-01 19:00:00] AppID 107410 update failed\n",
+        )
+        .unwrap();
+
+        let mut watch = LogWatch::new(d.path());
+        let mut log = fs::read_to_string(&console).unwrap();
+        log.push_str("[2024-05-01 20:00:00] GameAction [AppID 107410, ActionID 1] : LaunchApp changed task to FailedSetup with \"\"\n");
+        fs::write(&console, log).unwrap();
+
+        let err = watch
+            .wait("107410", Instant::now() + Duration::from_secs(2))
+            .unwrap_err();
+        assert!(err.to_string().contains("FailedSetup"), "{err}");
+    }
+}
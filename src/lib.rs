@@ -4,6 +4,10 @@
 Core capabilities:
 - Validate an Arma 3 installation directory
 - Launch via Steam (indirect) or direct execution
+- Read and edit Steam's text and binary VDF files (`VdfDocument`, `AppInfo`)
+- Add non-Steam library shortcuts (`SteamShortcut`)
+- Check the environment before launching (`doctor`)
+- Follow the game's RPT log (`GameSession::wait_for_rpt`) and analyze it (`Diagnostics`)
 
 Supported platforms:
 - Linux
@@ -13,15 +17,33 @@ Supported platforms:
 #[cfg(not(any(target_os = "linux", target_os = "windows")))]
 compile_error!("arma3-launcher currently supports Linux and Windows only.");
 
+mod doctor;
 mod error;
 mod install;
 mod launch;
 mod mods;
 mod platform;
+mod rpt;
 mod steam;
 
+pub use crate::doctor::{doctor, CheckStatus, DoctorCheck, DoctorReport};
 pub use crate::error::{Arma3Error, Result};
-pub use crate::install::{detect_best_install, detect_install_candidates};
+pub use crate::install::{
+    detect_best_install, detect_install_candidates, trace_install_detection, DetectionContext,
+    DetectionTrace, Probe,
+};
 pub use crate::install::{Arma3Install, InstallKind};
-pub use crate::launch::{LaunchMode, LaunchPlan, Launcher};
+pub use crate::launch::{
+    DllOverride, GameSession, InstanceCheck, LaunchMode, LaunchPlan, Launcher, ProtonOptions,
+};
 pub use crate::mods::{LocalMod, ModSet};
+pub use crate::rpt::{
+    find_rpt_files, CrashFile, CrashReport, Diagnostics, MissingAddon, RptMod, RptTail, ScriptError,
+};
+pub use crate::steam::{
+    add_steam_shortcut, detect_active_steam_account, detect_arma3_app_info, detect_compat_tools,
+    detect_steam_account_ids, detect_steam_accounts, detect_steam_installations, parse_appinfo,
+    read_app_info, read_arma3_launch_options, set_arma3_compat_tool, AppInfo, BinaryType,
+    CompatTool, CompatToolSource, SteamAccount, SteamInstallation, SteamPackage, SteamShortcut,
+    VdfDocument, VdfNode, VdfValue,
+};
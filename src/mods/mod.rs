@@ -3,7 +3,7 @@ use std::path::{Path, PathBuf};
 
 mod validate;
 
-use validate::validate_local_mod_dir;
+pub(crate) use validate::validate_local_mod_dir;
 
 /// A validated local mod directory.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -1 +1,3 @@
 pub(crate) mod path;
+pub(crate) mod process;
+pub(crate) mod tail;
@@ -0,0 +1,197 @@
+use crate::error::{Arma3Error, Result};
+use std::ffi::OsString;
+use std::path::Path;
+
+#[cfg(target_os = "linux")]
+use crate::steam::{ARMA3_APP_ID_STR, ENV_STEAM_GAME_ID};
+#[cfg(target_os = "linux")]
+use std::fs;
+
+/// Find a running game process: its executable name matches `exe_name` and it was started
+/// by Steam for Arma 3 (`SteamGameId`) or runs from `game_dir`.
+#[cfg(target_os = "linux")]
+pub(crate) fn find_game_pid(exe_name: &str, game_dir: &Path) -> Option<u32> {
+    let entries = fs::read_dir("/proc").ok()?;
+    entries
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+        .find(|pid| is_game_process(*pid, exe_name, game_dir))
+}
+
+#[cfg(target_os = "linux")]
+fn is_game_process(pid: u32, exe_name: &str, game_dir: &Path) -> bool {
+    let comm = fs::read_to_string(format!("/proc/{pid}/comm")).unwrap_or_default();
+    let cmdline = fs::read(format!("/proc/{pid}/cmdline")).unwrap_or_default();
+    let argv0 = cmdline.split(|b| *b == 0).next().unwrap_or_default();
+    let argv0 = String::from_utf8_lossy(argv0);
+    let argv0_name = argv0.rsplit(['/', '\\']).next().unwrap_or_default();
+    if !comm.trim().eq_ignore_ascii_case(exe_name) && !argv0_name.eq_ignore_ascii_case(exe_name) {
+        return false;
+    }
+
+    let game_id = format!("{ENV_STEAM_GAME_ID}={ARMA3_APP_ID_STR}");
+    let started_by_steam = fs::read(format!("/proc/{pid}/environ"))
+        .map(|env| env.split(|b| *b == 0).any(|kv| kv == game_id.as_bytes()))
+        .unwrap_or(false);
+    started_by_steam
+        || fs::read_link(format!("/proc/{pid}/cwd")).is_ok_and(|cwd| cwd.starts_with(game_dir))
+}
+
+/// True while `pid` exists and has not exited (zombies count as exited).
+#[cfg(target_os = "linux")]
+pub(crate) fn is_alive(pid: u32) -> bool {
+    let Ok(stat) = fs::read_to_string(format!("/proc/{pid}/stat")) else {
+        return false;
+    };
+    // The state follows the parenthesised command name, which may contain spaces.
+    let state = stat
+        .rsplit_once(')')
+        .and_then(|(_, rest)| rest.split_whitespace().next());
+    !matches!(state, Some("Z" | "X") | None)
+}
+
+/// Find a running game process by executable name.
+#[cfg(target_os = "windows")]
+pub(crate) fn find_game_pid(exe_name: &str, _game_dir: &Path) -> Option<u32> {
+    tasklist(&format!("IMAGENAME eq {exe_name}"))
+        .into_iter()
+        .next()
+}
+
+/// True while `pid` exists.
+#[cfg(target_os = "windows")]
+pub(crate) fn is_alive(pid: u32) -> bool {
+    tasklist(&format!("PID eq {pid}")).contains(&pid)
+}
+
+/// PIDs from `tasklist /FI <filter>`.
+#[cfg(target_os = "windows")]
+fn tasklist(filter: &str) -> Vec<u32> {
+    let Ok(out) = std::process::Command::new("tasklist")
+        .args(["/FI", filter, "/FO", "CSV", "/NH"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    // "arma3_x64.exe","1234","Console","1","1,234,567 K"
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(|line| line.split("\",\"").nth(1)?.parse().ok())
+        .collect()
+}
+
+/// `roots` and all their descendant processes.
+#[cfg(target_os = "linux")]
+pub(crate) fn process_tree(roots: &[u32]) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return roots.to_vec();
+    };
+    let parents: Vec<(u32, u32)> = entries
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| Some((pid, parent_pid(pid)?)))
+        .collect();
+
+    let mut tree = roots.to_vec();
+    let mut i = 0;
+    while i < tree.len() {
+        let parent = tree[i];
+        for (pid, _) in parents.iter().filter(|(_, ppid)| *ppid == parent) {
+            if !tree.contains(pid) {
+                tree.push(*pid);
+            }
+        }
+        i += 1;
+    }
+    tree
+}
+
+#[cfg(target_os = "linux")]
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let (_, rest) = stat.rsplit_once(')')?;
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Environment of a running process.
+#[cfg(target_os = "linux")]
+pub(crate) fn environ(pid: u32) -> Vec<(OsString, OsString)> {
+    use std::os::unix::ffi::OsStringExt;
+
+    let env = fs::read(format!("/proc/{pid}/environ")).unwrap_or_default();
+    env.split(|b| *b == 0)
+        .filter_map(|kv| {
+            let eq = kv.iter().position(|b| *b == b'=')?;
+            Some((
+                OsString::from_vec(kv[..eq].to_vec()),
+                OsString::from_vec(kv[eq + 1..].to_vec()),
+            ))
+        })
+        .collect()
+}
+
+/// `roots`; `taskkill /T` walks the tree itself.
+#[cfg(target_os = "windows")]
+pub(crate) fn process_tree(roots: &[u32]) -> Vec<u32> {
+    roots.to_vec()
+}
+
+/// Environment of a running process (not available on Windows).
+#[cfg(target_os = "windows")]
+pub(crate) fn environ(_pid: u32) -> Vec<(OsString, OsString)> {
+    Vec::new()
+}
+
+/// Ask processes to exit (`SIGTERM`, or `SIGKILL` when `force` is set; `taskkill /T` on Windows).
+///
+/// Processes that already exited are ignored.
+pub(crate) fn signal(pids: &[u32], force: bool) -> Result<()> {
+    if pids.is_empty() {
+        return Ok(());
+    }
+
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = std::process::Command::new("taskkill");
+        cmd.arg("/T");
+        if force {
+            cmd.arg("/F");
+        }
+        for pid in pids {
+            cmd.args(["/PID", &pid.to_string()]);
+        }
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = std::process::Command::new("kill");
+        cmd.arg(if force { "-KILL" } else { "-TERM" });
+        cmd.args(pids.iter().map(|p| p.to_string()));
+        cmd
+    };
+
+    cmd.stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map_err(|e| Arma3Error::Spawn {
+            message: format!("{e}"),
+        })?;
+    Ok(())
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_own_process_by_name_and_cwd() {
+        let me = std::process::id();
+        let comm = fs::read_to_string("/proc/self/comm").unwrap();
+        let cwd = std::env::current_dir().unwrap();
+        assert!(is_alive(me));
+        assert!(is_game_process(me, comm.trim(), &cwd));
+        assert!(!is_game_process(me, "arma3_x64.exe", &cwd));
+        assert!(process_tree(&[me]).contains(&me));
+        assert!(!environ(me).is_empty());
+    }
+}
@@ -0,0 +1,64 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Reads lines appended to a file since the last read.
+#[derive(Debug, Clone)]
+pub(crate) struct FileTail {
+    path: PathBuf,
+    offset: u64,
+    partial: String,
+}
+
+impl FileTail {
+    /// Follow `path` from its current end (or from the start if it does not exist yet).
+    pub(crate) fn from_end(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let offset = path.metadata().map(|m| m.len()).unwrap_or(0);
+        Self {
+            path,
+            offset,
+            partial: String::new(),
+        }
+    }
+
+    /// Follow `path` from its first line.
+    pub(crate) fn from_start(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            offset: 0,
+            partial: String::new(),
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Complete lines written since the last call; a missing file yields none.
+    pub(crate) fn read_lines(&mut self) -> std::io::Result<Vec<String>> {
+        let mut f = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        if f.metadata()?.len() < self.offset {
+            // Truncated or rotated.
+            self.offset = 0;
+            self.partial.clear();
+        }
+
+        let mut buf = Vec::new();
+        f.seek(SeekFrom::Start(self.offset))?;
+        f.read_to_end(&mut buf)?;
+        self.offset += buf.len() as u64;
+        self.partial.push_str(&String::from_utf8_lossy(&buf));
+
+        let mut lines = Vec::new();
+        while let Some(end) = self.partial.find('\n') {
+            lines.push(self.partial[..end].trim_end().to_string());
+            self.partial.drain(..=end);
+        }
+        Ok(lines)
+    }
+}
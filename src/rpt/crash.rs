@@ -0,0 +1,145 @@
+use crate::rpt::{
+    files_with_extensions, newest_rpt_since, with_start_slack, CRASH_DUMP_EXTENSIONS,
+};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::SystemTime;
+
+const LAST_RPT_LINES: usize = 50;
+
+/// What a crashed session left behind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashReport {
+    exit_status: Option<ExitStatus>,
+    dumps: Vec<CrashFile>,
+    rpt: Option<CrashFile>,
+    last_rpt_lines: Vec<String>,
+}
+
+/// A file written by the crashed session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashFile {
+    path: PathBuf,
+    modified: SystemTime,
+}
+
+impl CrashReport {
+    /// Build a report if the session that started at `started_at` crashed: it exited
+    /// unsuccessfully, or left crash dumps in `rpt_dir`.
+    pub(crate) fn detect(
+        rpt_dir: Option<&Path>,
+        started_at: SystemTime,
+        exit_status: Option<ExitStatus>,
+    ) -> Option<Self> {
+        let since = with_start_slack(started_at);
+        let dumps: Vec<CrashFile> = rpt_dir
+            .map(|dir| files_with_extensions(dir, &CRASH_DUMP_EXTENSIONS))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, modified)| *modified >= since)
+            .map(|(path, modified)| CrashFile { path, modified })
+            .collect();
+        if dumps.is_empty() && exit_status.map_or(true, |s| s.success()) {
+            return None;
+        }
+
+        let rpt = rpt_dir
+            .and_then(|dir| newest_rpt_since(dir, started_at))
+            .and_then(|path| {
+                let modified = path.metadata().ok()?.modified().ok()?;
+                Some(CrashFile { path, modified })
+            });
+        let last_rpt_lines = rpt
+            .as_ref()
+            .map(|rpt| last_lines(&rpt.path, LAST_RPT_LINES))
+            .unwrap_or_default();
+        Some(Self {
+            exit_status,
+            dumps,
+            rpt,
+            last_rpt_lines,
+        })
+    }
+
+    /// Exit status of the game, when known (direct launches).
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        self.exit_status
+    }
+
+    /// Crash dumps written during the session, newest first.
+    pub fn dumps(&self) -> &[CrashFile] {
+        &self.dumps
+    }
+
+    /// The session's RPT log.
+    pub fn rpt(&self) -> Option<&CrashFile> {
+        self.rpt.as_ref()
+    }
+
+    /// The last lines of the RPT (up to 50), usually written just before the crash.
+    pub fn last_rpt_lines(&self) -> &[String] {
+        &self.last_rpt_lines
+    }
+}
+
+impl CrashFile {
+    /// File path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Last modification time.
+    pub fn modified(&self) -> SystemTime {
+        self.modified
+    }
+}
+
+fn last_lines(path: &Path, count: usize) -> Vec<String> {
+    let Ok(bytes) = fs::read(path) else {
+        return Vec::new();
+    };
+    let text = String::from_utf8_lossy(&bytes);
+    let mut lines = VecDeque::with_capacity(count);
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        if lines.len() == count {
+            lines.pop_front();
+        }
+        lines.push_back(line.to_string());
+    }
+    lines.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn reports_new_crash_dumps_with_rpt_tail() {
+        let d = tempfile::tempdir().unwrap();
+        let started = SystemTime::now();
+        assert_eq!(CrashReport::detect(Some(d.path()), started, None), None);
+
+        let rpt = d.path().join("Arma3_x64_2024-05-01_20-00-00.rpt");
+        let log: String = (1..=60)
+            .map(|i| format!("20:00:{i:02} line {i}\n"))
+            .collect();
+        fs::write(&rpt, log).unwrap();
+        assert_eq!(CrashReport::detect(Some(d.path()), started, None), None);
+
+        let dump = d.path().join("Arma3_x64_2024-05-01_20-01-00.mdmp");
+        fs::write(&dump, "MDMP").unwrap();
+        fs::write(d.path().join("Arma3_x64_2024-05-01_20-01-00.bidmp"), "").unwrap();
+        let report = CrashReport::detect(Some(d.path()), started, None).unwrap();
+        assert_eq!(report.dumps().len(), 2);
+        assert!(report.dumps().iter().any(|f| f.path() == dump));
+        assert_eq!(report.rpt().unwrap().path(), rpt);
+        assert_eq!(report.last_rpt_lines().len(), LAST_RPT_LINES);
+        assert_eq!(report.last_rpt_lines().last().unwrap(), "20:00:60 line 60");
+
+        let later = SystemTime::now() + Duration::from_secs(3600);
+        assert_eq!(CrashReport::detect(Some(d.path()), later, None), None);
+    }
+}
@@ -0,0 +1,444 @@
+use crate::error::Result;
+use std::fs;
+use std::iter::Peekable;
+use std::path::Path;
+
+const MOD_LIST_MARKER: &str = "List of mods";
+const WARNING_MESSAGE_PREFIX: &str = "Warning Message: ";
+const MISSING_FILE_PREFIXES: [&str; 2] = ["Cannot open ", "Cannot load "];
+const MISSING_FILE_KINDS: [&str; 4] = ["object ", "texture ", "sound ", "file "];
+
+/// Findings from an Arma 3 `.rpt` log.
+///
+/// Repeated findings (e.g. a script error raised every frame) are reported once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    game_version: Option<String>,
+    allocator: Option<String>,
+    mods: Vec<RptMod>,
+    missing_addons: Vec<MissingAddon>,
+    missing_files: Vec<String>,
+    script_errors: Vec<ScriptError>,
+    warning_messages: Vec<String>,
+    battleye_kicks: Vec<String>,
+}
+
+/// A mod from the RPT's "List of mods" table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RptMod {
+    name: String,
+    dir: String,
+    hash: String,
+    hash_short: String,
+    path: String,
+}
+
+/// An addon that another addon depends on but that was not loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingAddon {
+    addon: String,
+    required_by: String,
+}
+
+/// A script error with the file and line it was raised from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    message: String,
+    expression: String,
+    file: Option<String>,
+    line: Option<u32>,
+}
+
+impl Diagnostics {
+    /// Read and analyze an RPT file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = fs::read(path)?;
+        Ok(Self::parse(&String::from_utf8_lossy(&bytes)))
+    }
+
+    /// Analyze RPT text.
+    pub fn parse(text: &str) -> Self {
+        let mut d = Self::default();
+        let mut lines = text.lines().map(strip_time).peekable();
+
+        while let Some(line) = lines.next() {
+            if d.game_version.is_none() {
+                if let Some(version) = line.strip_prefix("Version: ") {
+                    d.game_version = Some(version.trim().to_string());
+                    continue;
+                }
+            }
+            if d.allocator.is_none() {
+                if let Some(allocator) = line.strip_prefix("Allocator: ") {
+                    d.allocator = Some(allocator_name(allocator));
+                    continue;
+                }
+            }
+            if line.contains(MOD_LIST_MARKER) {
+                d.mods = parse_mod_table(&mut lines);
+                continue;
+            }
+            if let Some(expression) = line.strip_prefix("Error in expression <") {
+                let error = parse_script_error(expression, &mut lines);
+                push_unique(&mut d.script_errors, error);
+                continue;
+            }
+
+            let message = match line.strip_prefix(WARNING_MESSAGE_PREFIX) {
+                Some(message) => {
+                    push_unique(&mut d.warning_messages, message.trim().to_string());
+                    message
+                }
+                None => line,
+            };
+            if let Some(missing) = parse_missing_addon(message) {
+                push_unique(&mut d.missing_addons, missing);
+            } else if let Some(file) = parse_missing_file(message) {
+                push_unique(&mut d.missing_files, file);
+            } else if let Some(reason) = parse_battleye_kick(message) {
+                push_unique(&mut d.battleye_kicks, reason);
+            }
+        }
+        d
+    }
+
+    /// Game version from the RPT header (e.g. `2.16.151618`).
+    pub fn game_version(&self) -> Option<&str> {
+        self.game_version.as_deref()
+    }
+
+    /// File name of the memory allocator in use (e.g. `tbb4malloc_bi_x64.dll`).
+    pub fn allocator(&self) -> Option<&str> {
+        self.allocator.as_deref()
+    }
+
+    /// Loaded mods, including the official content the game lists.
+    pub fn mods(&self) -> &[RptMod] {
+        &self.mods
+    }
+
+    /// Addons required by a loaded addon but not loaded themselves.
+    pub fn missing_addons(&self) -> &[MissingAddon] {
+        &self.missing_addons
+    }
+
+    /// Models, textures, sounds and scripts the game could not open.
+    pub fn missing_files(&self) -> &[String] {
+        &self.missing_files
+    }
+
+    /// Script errors.
+    pub fn script_errors(&self) -> &[ScriptError] {
+        &self.script_errors
+    }
+
+    /// Messages shown to the player in a `Warning Message:` popup.
+    pub fn warning_messages(&self) -> &[String] {
+        &self.warning_messages
+    }
+
+    /// BattlEye kick reasons.
+    pub fn battleye_kicks(&self) -> &[String] {
+        &self.battleye_kicks
+    }
+
+    /// True if any problem was found (missing addons or files, script errors, popups, kicks).
+    pub fn has_problems(&self) -> bool {
+        !(self.missing_addons.is_empty()
+            && self.missing_files.is_empty()
+            && self.script_errors.is_empty()
+            && self.warning_messages.is_empty()
+            && self.battleye_kicks.is_empty())
+    }
+}
+
+impl RptMod {
+    /// Display name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Mod directory name (e.g. `@CBA_A3`).
+    pub fn dir(&self) -> &str {
+        &self.dir
+    }
+
+    /// Content hash.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// Short content hash.
+    pub fn hash_short(&self) -> &str {
+        &self.hash_short
+    }
+
+    /// Full path as seen by the game (a Wine path under Proton); empty if not found.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl MissingAddon {
+    /// The addon that is missing.
+    pub fn addon(&self) -> &str {
+        &self.addon
+    }
+
+    /// The addon that requires it.
+    pub fn required_by(&self) -> &str {
+        &self.required_by
+    }
+}
+
+impl ScriptError {
+    /// Error message (e.g. `Undefined variable in expression: _dmg`).
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The failing expression.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Script file, if the RPT names it.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// Line in [`ScriptError::file`].
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+}
+
+/// Drop the `hh:mm:ss ` prefix the game writes before each log line.
+fn strip_time(line: &str) -> &str {
+    let trimmed = line.trim_start();
+    let (time, rest) = trimmed.split_once(' ').unwrap_or((trimmed, ""));
+    let is_time = time.split(':').count() == 3
+        && time
+            .split(':')
+            .all(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()));
+    if is_time {
+        rest.trim()
+    } else {
+        line.trim()
+    }
+}
+
+/// `C:\...\Dll\tbb4malloc_bi_x64.dll [2017.0.0.0] [2017.0.0.0]` -> `tbb4malloc_bi_x64.dll`.
+fn allocator_name(value: &str) -> String {
+    let path = value.split(" [").next().unwrap_or(value).trim();
+    path.rsplit(['\\', '/']).next().unwrap_or(path).to_string()
+}
+
+/// Rows of the mod table following the "List of mods" line, up to its closing rule.
+fn parse_mod_table<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Vec<RptMod> {
+    let mut columns: Option<Vec<&str>> = None;
+    let mut mods = Vec::new();
+    for line in lines {
+        let is_rule = !line.is_empty() && line.bytes().all(|b| b == b'=');
+        let Some(columns) = &columns else {
+            if line.contains('|') {
+                columns = Some(line.split('|').map(str::trim).collect());
+            }
+            continue;
+        };
+        if is_rule {
+            if mods.is_empty() {
+                continue;
+            }
+            break;
+        }
+        if !line.contains('|') {
+            break;
+        }
+
+        let cells: Vec<&str> = line.split('|').map(str::trim).collect();
+        let cell = |name: &str| {
+            columns
+                .iter()
+                .position(|c| *c == name)
+                .and_then(|i| cells.get(i))
+                .map_or_else(String::new, |c| c.to_string())
+        };
+        mods.push(RptMod {
+            name: cell("name"),
+            dir: cell("modDir"),
+            hash: cell("hash"),
+            hash_short: cell("hashShort"),
+            path: cell("fullPath"),
+        });
+    }
+    mods
+}
+
+/// An `Error in expression <...>` block: the expression may span several lines, followed by
+/// `Error position:`, the error message and usually `File ..., line N`.
+fn parse_script_error<'a, I: Iterator<Item = &'a str>>(
+    first: &str,
+    lines: &mut Peekable<I>,
+) -> ScriptError {
+    let mut expression = first.to_string();
+    while !expression.ends_with('>') {
+        match lines.next() {
+            Some(line) => {
+                expression.push('\n');
+                expression.push_str(line);
+            }
+            None => break,
+        }
+    }
+    let mut error = ScriptError {
+        message: String::new(),
+        expression: expression.trim_end_matches('>').to_string(),
+        file: None,
+        line: None,
+    };
+
+    let mut in_position = false;
+    while let Some(line) = lines.peek().copied() {
+        if in_position || line.starts_with("Error position: <") {
+            in_position = !line.ends_with('>');
+        } else if let Some(message) = line.strip_prefix("Error ") {
+            error.message = message.trim().to_string();
+        } else if let Some(location) = line.strip_prefix("File ") {
+            let (file, line_no) = location.rsplit_once(", line ").unwrap_or((location, ""));
+            let file = file.trim_end_matches("...");
+            let file = file.split(" [").next().unwrap_or(file);
+            error.file = Some(file.trim().to_string());
+            error.line = line_no.trim().parse().ok();
+            lines.next();
+            break;
+        } else {
+            // Code run from the debug console has no `File` line.
+            break;
+        }
+        lines.next();
+    }
+    error
+}
+
+/// `Addon 'mymod_main' requires addon 'cba_xeh'`.
+fn parse_missing_addon(message: &str) -> Option<MissingAddon> {
+    let (by, addon) = message.split_once(" requires addon ")?;
+    let by = by.strip_prefix("Addon ")?;
+    let unquote = |s: &str| s.trim().trim_matches('\'').to_string();
+    Some(MissingAddon {
+        addon: unquote(addon),
+        required_by: unquote(by),
+    })
+}
+
+/// `Cannot open object \x\y.p3d`, `Cannot load texture \x\y.paa.`, `Script \x\y.sqf not found`.
+fn parse_missing_file(message: &str) -> Option<String> {
+    let path = match MISSING_FILE_PREFIXES
+        .iter()
+        .find_map(|p| message.strip_prefix(p))
+    {
+        Some(rest) => MISSING_FILE_KINDS
+            .iter()
+            .find_map(|k| rest.strip_prefix(k))
+            .unwrap_or(rest)
+            .trim_end_matches('.'),
+        None => message
+            .strip_prefix("Script ")?
+            .strip_suffix(" not found")?,
+    };
+    let path = path.trim().trim_matches('\'');
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+/// `You were kicked off the game. (BattlEye: Client not responding)` or a server's
+/// `Player #3 Miller (...) has been kicked by BattlEye: Script Restriction #12`.
+fn parse_battleye_kick(message: &str) -> Option<String> {
+    if !message.to_ascii_lowercase().contains("kicked") {
+        return None;
+    }
+    let (_, reason) = message.split_once("BattlEye: ")?;
+    Some(reason.trim().trim_end_matches(')').trim().to_string())
+}
+
+fn push_unique<T: PartialEq>(items: &mut Vec<T>, item: T) {
+    if !items.contains(&item) {
+        items.push(item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOWS_MODDED: &str = include_str!("../../tests/fixtures/rpt/windows_modded.rpt");
+    const PROTON_BATTLEYE: &str = include_str!("../../tests/fixtures/rpt/proton_battleye.rpt");
+
+    #[test]
+    fn analyzes_modded_rpt() {
+        let d = Diagnostics::parse(WINDOWS_MODDED);
+        assert_eq!(d.game_version(), Some("2.16.151618"));
+        assert_eq!(d.allocator(), Some("tbb4malloc_bi_x64.dll"));
+
+        let dirs: Vec<&str> = d.mods().iter().map(|m| m.dir()).collect();
+        assert_eq!(dirs, ["@mymod", "@ace", "@CBA_A3", "enoch", "A3"]);
+        let cba = &d.mods()[2];
+        assert_eq!(cba.name(), "Community Base Addons v3.17.1.240424");
+        assert_eq!(cba.hash(), "8d1b8b5e2a3c7d3d1e0b33f1c1f9e8a1d5a9b2c3");
+        assert_eq!(cba.hash_short(), "2d5f0e1a");
+        assert_eq!(cba.path(), r"C:\Mods\@CBA_A3");
+        assert_eq!(d.mods()[3].path(), "");
+
+        let missing: Vec<(&str, &str)> = d
+            .missing_addons()
+            .iter()
+            .map(|m| (m.addon(), m.required_by()))
+            .collect();
+        assert_eq!(
+            missing,
+            [("cba_xeh", "mymod_main"), ("ace_interact_menu", "mymod_ui")]
+        );
+        assert_eq!(
+            d.missing_files(),
+            [
+                r"\mymod\addons\main\data\crate.p3d",
+                r"\mymod\addons\ui\data\icon_ca.paa",
+                r"\x\mymod\addons\main\XEH_postInit.sqf",
+            ]
+        );
+
+        assert_eq!(d.script_errors().len(), 2);
+        let heal = &d.script_errors()[0];
+        assert_eq!(heal.message(), "Undefined variable in expression: _dmg");
+        assert_eq!(
+            heal.expression(),
+            "_unit setDamage _dmg;\n_unit setVariable [\"mymod_healed\", true];"
+        );
+        assert_eq!(
+            heal.file(),
+            Some(r"\x\mymod\addons\main\functions\fn_heal.sqf")
+        );
+        assert_eq!(heal.line(), Some(12));
+        let init = &d.script_errors()[1];
+        assert_eq!(init.file(), Some(r"mpmissions\__cur_mp.Altis\init.sqf"));
+        assert_eq!(init.line(), Some(3));
+
+        assert_eq!(d.warning_messages().len(), 5);
+        assert_eq!(
+            d.warning_messages()[4],
+            "No entry 'bin\\config.bin/CfgWeapons.mymod_rifle'."
+        );
+        assert!(d.battleye_kicks().is_empty());
+        assert!(d.has_problems());
+    }
+
+    #[test]
+    fn analyzes_battleye_kick_under_proton() {
+        let d = Diagnostics::parse(PROTON_BATTLEYE);
+        assert_eq!(d.allocator(), Some("mimalloc_v212_LockPages.dll"));
+        assert_eq!(d.mods().len(), 1);
+        assert_eq!(d.battleye_kicks(), ["Client not responding"]);
+        assert!(d.missing_addons().is_empty());
+        assert!(d.script_errors().is_empty());
+    }
+}
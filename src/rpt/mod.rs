@@ -0,0 +1,132 @@
+mod crash;
+mod diagnostics;
+
+pub use crash::{CrashFile, CrashReport};
+pub use diagnostics::{Diagnostics, MissingAddon, RptMod, ScriptError};
+
+use crate::error::Result;
+use crate::install::Arma3Install;
+use crate::platform::tail::FileTail;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub(crate) const RPT_EXTENSION: &str = "rpt";
+/// Minidumps (`.mdmp`) and BattlEye dumps (`.bidmp`) written next to the RPT on a crash.
+pub(crate) const CRASH_DUMP_EXTENSIONS: [&str; 2] = ["mdmp", "bidmp"];
+/// Allowance for an RPT created just before the session recorded its start time.
+const SESSION_START_SLACK: Duration = Duration::from_secs(5);
+
+/// Follows an Arma 3 `.rpt` log while the game writes it.
+///
+/// ```no_run
+/// use arma3_launcher::{Arma3Install, Launcher};
+///
+/// # fn main() -> arma3_launcher::Result<()> {
+/// let install = Arma3Install::new("/home/me/.local/share/Steam/steamapps/common/Arma 3")?;
+/// let session = Launcher::new(install).start()?;
+/// if let Some(mut rpt) = session.wait_for_rpt(std::time::Duration::from_secs(60))? {
+///     while session.is_running() {
+///         for line in rpt.read_lines()? {
+///             println!("{line}");
+///         }
+///         std::thread::sleep(std::time::Duration::from_millis(500));
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RptTail {
+    tail: FileTail,
+}
+
+impl RptTail {
+    /// Follow `path` from its first line.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        Self {
+            tail: FileTail::from_start(path),
+        }
+    }
+
+    /// RPT file path.
+    pub fn path(&self) -> &Path {
+        self.tail.path()
+    }
+
+    /// Complete lines written since the last call (all lines on the first call).
+    ///
+    /// Does not block; returns an empty list when nothing new was written.
+    pub fn read_lines(&mut self) -> Result<Vec<String>> {
+        Ok(self.tail.read_lines()?)
+    }
+}
+
+/// `.rpt` logs written by `install`, newest first.
+pub fn find_rpt_files(install: &Arma3Install) -> Result<Vec<PathBuf>> {
+    Ok(files_with_extensions(&install.rpt_dir()?, &[RPT_EXTENSION])
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect())
+}
+
+/// Newest `.rpt` in `dir` that was written to at or after `since`.
+pub(crate) fn newest_rpt_since(dir: &Path, since: SystemTime) -> Option<PathBuf> {
+    let since = with_start_slack(since);
+    files_with_extensions(dir, &[RPT_EXTENSION])
+        .into_iter()
+        .find(|(_, modified)| *modified >= since)
+        .map(|(path, _)| path)
+}
+
+fn with_start_slack(since: SystemTime) -> SystemTime {
+    since.checked_sub(SESSION_START_SLACK).unwrap_or(since)
+}
+
+/// Files in `dir` with one of `extensions`, with their modification times, newest first.
+pub(crate) fn files_with_extensions(dir: &Path, extensions: &[&str]) -> Vec<(PathBuf, SystemTime)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(PathBuf, SystemTime)> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|e| extensions.iter().any(|x| e.eq_ignore_ascii_case(x)))
+        })
+        .filter_map(|p| {
+            let modified = p.metadata().ok()?.modified().ok()?;
+            Some((p, modified))
+        })
+        .collect();
+    files.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_session_rpt_and_streams_lines() {
+        let d = tempfile::tempdir().unwrap();
+        fs::write(d.path().join("Arma3.cfg"), "").unwrap();
+        assert_eq!(newest_rpt_since(d.path(), SystemTime::UNIX_EPOCH), None);
+
+        let rpt = d.path().join("Arma3_x64_2024-05-01_20-00-00.rpt");
+        fs::write(&rpt, "=====\nMods: @cba\npartial").unwrap();
+        assert_eq!(
+            newest_rpt_since(d.path(), SystemTime::UNIX_EPOCH),
+            Some(rpt.clone())
+        );
+        let later = SystemTime::now() + Duration::from_secs(3600);
+        assert_eq!(newest_rpt_since(d.path(), later), None);
+
+        let mut tail = RptTail::open(&rpt);
+        assert_eq!(tail.read_lines().unwrap(), ["=====", "Mods: @cba"]);
+        fs::write(&rpt, "=====\nMods: @cba\npartial line\nnext\n").unwrap();
+        assert_eq!(tail.read_lines().unwrap(), ["partial line", "next"]);
+        assert!(tail.read_lines().unwrap().is_empty());
+    }
+}
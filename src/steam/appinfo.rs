@@ -0,0 +1,278 @@
+use crate::error::{Arma3Error, Result};
+use crate::install::DetectionContext;
+use crate::steam::vdf::{VdfDocument, VdfNode};
+use crate::steam::vdf_binary::{self, Reader};
+use crate::steam::ARMA3_APP_ID;
+use std::fs;
+use std::path::Path;
+
+const APPINFO_REL: &str = "appcache/appinfo.vdf";
+const APPINFO_MAGIC_PREFIX: u32 = 0x0756_4400;
+const APPINFO_V27: u32 = 0x27;
+const APPINFO_V28: u32 = 0x28;
+const APPINFO_V29: u32 = 0x29;
+
+/// One app's entry from Steam's `appcache/appinfo.vdf`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppInfo {
+    app_id: u32,
+    info_state: u32,
+    last_updated: u32,
+    change_number: u32,
+    data: VdfDocument,
+}
+
+impl AppInfo {
+    /// Steam app ID.
+    pub fn app_id(&self) -> u32 {
+        self.app_id
+    }
+
+    /// Steam's info state flags for the entry.
+    pub fn info_state(&self) -> u32 {
+        self.info_state
+    }
+
+    /// Unix time the entry was last updated.
+    pub fn last_updated(&self) -> u32 {
+        self.last_updated
+    }
+
+    /// PICS change number.
+    pub fn change_number(&self) -> u32 {
+        self.change_number
+    }
+
+    /// App data tree (root key `appinfo`, with `common`, `config`, `depots`, `extended`, ...).
+    pub fn data(&self) -> &VdfDocument {
+        &self.data
+    }
+
+    /// Launch configurations (`appinfo/config/launch/<n>`), in file order.
+    pub fn launch_configs(&self) -> &[VdfNode] {
+        self.data
+            .get("appinfo/config/launch")
+            .map(VdfNode::children)
+            .unwrap_or_default()
+    }
+
+    /// Depot entries (`appinfo/depots/<depot id>`), skipping metadata such as `branches`.
+    pub fn depots(&self) -> impl Iterator<Item = &VdfNode> {
+        self.data
+            .get("appinfo/depots")
+            .map(VdfNode::children)
+            .unwrap_or_default()
+            .iter()
+            .filter(|n| n.key().parse::<u32>().is_ok())
+    }
+
+    /// DLC app IDs from `appinfo/extended/listofdlc`.
+    pub fn dlc_app_ids(&self) -> Vec<u32> {
+        self.data
+            .get_str("appinfo/extended/listofdlc")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|id| id.trim().parse().ok())
+            .collect()
+    }
+}
+
+/// Read Arma 3's entry from the detected Steam installation's `appcache/appinfo.vdf`.
+pub fn detect_arma3_app_info() -> Result<Option<AppInfo>> {
+    let steam_root = crate::steam::detect::detect_steam_root(&DetectionContext::new())
+        .ok_or(Arma3Error::SteamNotFound)?;
+    read_app_info(&steam_root.join(APPINFO_REL), ARMA3_APP_ID)
+}
+
+/// Parse every entry of an `appinfo.vdf` file (format versions 0x27, 0x28 and 0x29).
+pub fn parse_appinfo(bytes: &[u8]) -> Result<Vec<AppInfo>> {
+    let mut out = Vec::new();
+    scan_appinfo(
+        bytes,
+        |_| true,
+        |info| {
+            out.push(info);
+            true
+        },
+    )?;
+    Ok(out)
+}
+
+/// Read the entry for `app_id` from an `appinfo.vdf` file, skipping other apps unparsed.
+pub fn read_app_info(path: &Path, app_id: u32) -> Result<Option<AppInfo>> {
+    let bytes = fs::read(path).map_err(|e| Arma3Error::SteamConfig {
+        message: format!("failed reading {}: {e}", path.display()),
+    })?;
+    let mut found = None;
+    scan_appinfo(
+        &bytes,
+        |id| id == app_id,
+        |info| {
+            found = Some(info);
+            false
+        },
+    )?;
+    Ok(found)
+}
+
+/// Walk entries; `want` filters by app ID before parsing, `visit` returns false to stop.
+fn scan_appinfo(
+    bytes: &[u8],
+    want: impl Fn(u32) -> bool,
+    mut visit: impl FnMut(AppInfo) -> bool,
+) -> Result<()> {
+    let mut r = Reader::new(bytes);
+    let magic = r.u32()?;
+    let version = magic & 0xFF;
+    if magic & 0xFFFF_FF00 != APPINFO_MAGIC_PREFIX
+        || !(APPINFO_V27..=APPINFO_V29).contains(&version)
+    {
+        return Err(Arma3Error::SteamConfig {
+            message: format!("unsupported appinfo.vdf magic 0x{magic:08x}"),
+        });
+    }
+    let _universe = r.u32()?;
+
+    let strings = if version >= APPINFO_V29 {
+        let offset = r.u64()? as usize;
+        Some(read_string_table(bytes, offset)?)
+    } else {
+        None
+    };
+
+    loop {
+        let app_id = r.u32()?;
+        if app_id == 0 {
+            return Ok(());
+        }
+        let size = r.u32()? as usize;
+        let entry = r.take(size)?;
+        if !want(app_id) {
+            continue;
+        }
+
+        let mut e = Reader::new(entry);
+        let info_state = e.u32()?;
+        let last_updated = e.u32()?;
+        let _pics_token = e.u64()?;
+        let _sha1 = e.take(20)?;
+        let change_number = e.u32()?;
+        if version >= APPINFO_V28 {
+            let _binary_sha1 = e.take(20)?;
+        }
+        let nodes = vdf_binary::parse(&entry[e.i..], strings.as_deref())?;
+
+        let keep_going = visit(AppInfo {
+            app_id,
+            info_state,
+            last_updated,
+            change_number,
+            data: VdfDocument::from_nodes(nodes),
+        });
+        if !keep_going {
+            return Ok(());
+        }
+    }
+}
+
+fn read_string_table(bytes: &[u8], offset: usize) -> Result<Vec<String>> {
+    let table = bytes.get(offset..).ok_or_else(|| Arma3Error::SteamConfig {
+        message: format!("appinfo.vdf string table offset {offset} out of range"),
+    })?;
+    let mut r = Reader::new(table);
+    let count = r.u32()? as usize;
+    let mut strings = Vec::with_capacity(count.min(table.len()));
+    for _ in 0..count {
+        strings.push(r.cstring()?);
+    }
+    Ok(strings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(app_id: u32, kv: &[u8], with_binary_sha1: bool) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&2u32.to_le_bytes()); // info state
+        body.extend_from_slice(&1_700_000_000u32.to_le_bytes());
+        body.extend_from_slice(&0u64.to_le_bytes());
+        body.extend_from_slice(&[0u8; 20]);
+        body.extend_from_slice(&42u32.to_le_bytes());
+        if with_binary_sha1 {
+            body.extend_from_slice(&[0u8; 20]);
+        }
+        body.extend_from_slice(kv);
+
+        let mut out = Vec::new();
+        out.extend_from_slice(&app_id.to_le_bytes());
+        out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        out.extend_from_slice(&body);
+        out
+    }
+
+    #[test]
+    fn reads_v29_with_string_table() {
+        // Keys: 0 = appinfo, 1 = common, 2 = name, 3 = appid
+        let mut kv = Vec::new();
+        kv.extend_from_slice(&[0x00]);
+        kv.extend_from_slice(&0u32.to_le_bytes());
+        kv.extend_from_slice(&[0x02]);
+        kv.extend_from_slice(&3u32.to_le_bytes());
+        kv.extend_from_slice(&107_410u32.to_le_bytes());
+        kv.extend_from_slice(&[0x00]);
+        kv.extend_from_slice(&1u32.to_le_bytes());
+        kv.extend_from_slice(&[0x01]);
+        kv.extend_from_slice(&2u32.to_le_bytes());
+        kv.extend_from_slice(b"Arma 3\x00");
+        kv.extend_from_slice(&[0x08, 0x08, 0x08]);
+
+        let mut entries = entry(107_410, &kv, true);
+        entries.extend_from_slice(&0u32.to_le_bytes());
+
+        let mut file = Vec::new();
+        file.extend_from_slice(&0x0756_4429u32.to_le_bytes());
+        file.extend_from_slice(&1u32.to_le_bytes());
+        let table_offset = (16 + entries.len()) as u64;
+        file.extend_from_slice(&table_offset.to_le_bytes());
+        file.extend_from_slice(&entries);
+        file.extend_from_slice(&4u32.to_le_bytes());
+        file.extend_from_slice(b"appinfo\x00common\x00name\x00appid\x00");
+
+        let apps = parse_appinfo(&file).unwrap();
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].app_id(), 107_410);
+        assert_eq!(apps[0].change_number(), 42);
+        assert_eq!(apps[0].data().get_str("appinfo/appid"), Some("107410"));
+        assert_eq!(
+            apps[0].data().get_str("appinfo/common/name"),
+            Some("Arma 3")
+        );
+    }
+
+    #[test]
+    fn finds_one_app_in_v28() {
+        let other = entry(10, b"\x00appinfo\x00\x01name\x00Other\x00\x08\x08", true);
+        let arma = entry(
+            107_410,
+            b"\x00appinfo\x00\x01name\x00Arma 3\x00\x00extended\x00\x01listofdlc\x00288520,304380\x00\x08\x08\x08",
+            true,
+        );
+
+        let mut file = Vec::new();
+        file.extend_from_slice(&0x0756_4428u32.to_le_bytes());
+        file.extend_from_slice(&1u32.to_le_bytes());
+        file.extend_from_slice(&other);
+        file.extend_from_slice(&arma);
+        file.extend_from_slice(&0u32.to_le_bytes());
+
+        let d = tempfile::tempdir().unwrap();
+        let path = d.path().join("appinfo.vdf");
+        fs::write(&path, file).unwrap();
+
+        let info = read_app_info(&path, 107_410).unwrap().unwrap();
+        assert_eq!(info.data().get_str("appinfo/name"), Some("Arma 3"));
+        assert_eq!(info.dlc_app_ids(), [288_520, 304_380]);
+        assert!(read_app_info(&path, 999).unwrap().is_none());
+    }
+}
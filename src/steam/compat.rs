@@ -1,56 +1,552 @@
 use crate::error::{Arma3Error, Result};
-use crate::steam::vdf::Vdf;
+use crate::install::DetectionContext;
+use crate::steam::vdf::{Vdf, VdfDocument};
+use crate::steam::{
+    library, process, ARMA3_APP_ID_STR, STEAM_LINUX_RUNTIME_SCOUT_APP_ID,
+    STEAM_LINUX_RUNTIME_SNIPER_APP_ID, STEAM_LINUX_RUNTIME_SOLDIER_APP_ID,
+};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-pub(crate) fn compat_tool_shortname(steam_root: &Path, app_id: &str) -> Result<String> {
+const SYSTEM_COMPAT_TOOL_DIRS: [&str; 3] = [
+    "/usr/share/steam/compatibilitytools.d",
+    "/usr/local/share/steam/compatibilitytools.d",
+    "/usr/lib/steam/compatibilitytools.d",
+];
+
+/// Where a compatibility tool is installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompatToolSource {
+    /// Valve-distributed Proton in a Steam library (`steamapps/common/Proton*`).
+    Official,
+    /// User-installed tool in `<steam root>/compatibilitytools.d` (e.g. GE-Proton).
+    User,
+    /// System-wide tool (e.g. `/usr/share/steam/compatibilitytools.d`).
+    System,
+}
+
+/// An installed Steam compatibility tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatTool {
+    name: String,
+    display_name: String,
+    dir: PathBuf,
+    source: CompatToolSource,
+}
+
+impl CompatTool {
+    /// Internal name, as used in Steam's `CompatToolMapping` (e.g. `proton_9`, `GE-Proton9-20`).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Name shown in the Steam UI.
+    pub fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    /// Tool directory (contains `toolmanifest.vdf`).
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Where the tool is installed.
+    pub fn source(&self) -> CompatToolSource {
+        self.source
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.name == name || self.display_name == name
+    }
+}
+
+/// List compatibility tools available to the detected Steam installation.
+///
+/// Official Proton builds come first (newest version first), followed by user and system
+/// tools. Returns an empty list if Steam is not found.
+pub fn detect_compat_tools() -> Vec<CompatTool> {
+    DetectionContext::new().detect_compat_tools()
+}
+
+impl DetectionContext {
+    /// [`detect_compat_tools`] using this context.
+    pub fn detect_compat_tools(&self) -> Vec<CompatTool> {
+        match crate::steam::detect::detect_steam_root(self) {
+            Some(root) => compat_tools(self, &root),
+            None => Vec::new(),
+        }
+    }
+}
+
+pub(crate) fn compat_tools(ctx: &DetectionContext, steam_root: &Path) -> Vec<CompatTool> {
+    let mut official: Vec<(Vec<u32>, CompatTool)> = Vec::new();
+    for lib in library::steam_library_roots(steam_root) {
+        let Ok(entries) = fs::read_dir(lib.join("steamapps/common")) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            let dir_name = entry.file_name().to_string_lossy().to_string();
+            if !dir_name.starts_with("Proton") || !dir.join("toolmanifest.vdf").is_file() {
+                continue;
+            }
+            let tools = read_compatibilitytool_vdf(&dir, CompatToolSource::Official);
+            if tools.is_empty() {
+                official.push((
+                    official_proton_version(&dir_name),
+                    CompatTool {
+                        name: official_proton_name(&dir_name),
+                        display_name: dir_name,
+                        dir,
+                        source: CompatToolSource::Official,
+                    },
+                ));
+            } else {
+                let version = official_proton_version(&dir_name);
+                official.extend(tools.into_iter().map(|t| (version.clone(), t)));
+            }
+        }
+    }
+    official.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.name.cmp(&b.1.name)));
+
+    let mut out: Vec<CompatTool> = official.into_iter().map(|(_, t)| t).collect();
+    out.extend(tools_in_dir(
+        &steam_root.join("compatibilitytools.d"),
+        CompatToolSource::User,
+    ));
+    for dir in SYSTEM_COMPAT_TOOL_DIRS {
+        out.extend(tools_in_dir(
+            &ctx.system_path(dir),
+            CompatToolSource::System,
+        ));
+    }
+
+    let mut seen = std::collections::BTreeSet::new();
+    out.retain(|t| seen.insert(t.name.clone()));
+    out
+}
+
+fn tools_in_dir(parent: &Path, source: CompatToolSource) -> Vec<CompatTool> {
+    let Ok(entries) = fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    dirs.sort();
+
+    let mut out = Vec::new();
+    for dir in dirs {
+        let tools = read_compatibilitytool_vdf(&dir, source);
+        if !tools.is_empty() {
+            out.extend(tools);
+        } else if dir.join("toolmanifest.vdf").is_file() {
+            let name = dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            out.push(CompatTool {
+                display_name: name.clone(),
+                name,
+                dir,
+                source,
+            });
+        }
+    }
+    out
+}
+
+/// Parse `compatibilitytool.vdf`, which may declare several tools with relative install paths.
+fn read_compatibilitytool_vdf(dir: &Path, source: CompatToolSource) -> Vec<CompatTool> {
+    let Ok(txt) = fs::read_to_string(dir.join("compatibilitytool.vdf")) else {
+        return Vec::new();
+    };
+    let Ok(vdf) = Vdf::parse(&txt) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<&str> = Vec::new();
+    for k in vdf.kv.keys() {
+        let mut parts = k.split('/');
+        if parts.next() != Some("compatibilitytools") || parts.next() != Some("compat_tools") {
+            continue;
+        }
+        if let Some(name) = parts.next() {
+            if names.last() != Some(&name) {
+                names.push(name);
+            }
+        }
+    }
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let prefix = format!("compatibilitytools/compat_tools/{name}");
+            let install_path = vdf
+                .get(&format!("{prefix}/install_path"))
+                .map(String::as_str)
+                .unwrap_or(".");
+            let tool_dir = if install_path == "." {
+                dir.to_path_buf()
+            } else {
+                dir.join(install_path)
+            };
+            if !tool_dir.join("toolmanifest.vdf").is_file() {
+                return None;
+            }
+            let display_name = vdf
+                .get(&format!("{prefix}/display_name"))
+                .cloned()
+                .unwrap_or_else(|| name.to_string());
+            Some(CompatTool {
+                name: name.to_string(),
+                display_name,
+                dir: tool_dir,
+                source,
+            })
+        })
+        .collect()
+}
+
+/// Steam's internal name for an official Proton directory, e.g. `Proton 9.0 (Beta)` -> `proton_9`,
+/// `Proton 5.13` -> `proton_513`, `Proton - Experimental` -> `proton_experimental`.
+fn official_proton_name(dir_name: &str) -> String {
+    let rest = dir_name
+        .trim_start_matches("Proton")
+        .trim_start_matches([' ', '-'])
+        .trim();
+    let version: Vec<u32> = official_proton_version(dir_name);
+    if version.is_empty() {
+        let word = rest.split_whitespace().next().unwrap_or("");
+        return format!("proton_{}", word.to_ascii_lowercase());
+    }
+    let mut name = format!("proton_{}", version[0]);
+    if let Some(minor) = version.get(1).filter(|m| **m != 0) {
+        name.push_str(&minor.to_string());
+    }
+    name
+}
+
+fn official_proton_version(dir_name: &str) -> Vec<u32> {
+    dir_name
+        .trim_start_matches("Proton")
+        .split_whitespace()
+        .next()
+        .map(|v| v.split('.').map_while(|n| n.parse::<u32>().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Tool Steam maps to `app_id` in `config/config.vdf`, falling back to the global default (`0`).
+pub(crate) fn mapped_compat_tool_name(steam_root: &Path, app_id: &str) -> Result<Option<String>> {
     let config_vdf_path = steam_root.join("config/config.vdf");
+    if !config_vdf_path.is_file() {
+        return Ok(None);
+    }
     let config_txt = fs::read_to_string(&config_vdf_path).map_err(|e| Arma3Error::SteamConfig {
         message: format!("failed reading {}: {e}", config_vdf_path.display()),
     })?;
     let vdf = Vdf::parse(&config_txt)?;
 
-    let keys = [
-        format!("InstallConfigStore/Software/Valve/Steam/CompatToolMapping/{app_id}/name"),
-        format!("Software/Valve/Steam/CompatToolMapping/{app_id}/name"),
-        format!("CompatToolMapping/{app_id}/name"),
-    ];
-
-    for key in keys {
-        if let Some(v) = vdf.get(&key) {
-            return Ok(v.clone());
+    for id in [app_id, "0"] {
+        let keys = [
+            format!("InstallConfigStore/Software/Valve/Steam/CompatToolMapping/{id}/name"),
+            format!("Software/Valve/Steam/CompatToolMapping/{id}/name"),
+            format!("CompatToolMapping/{id}/name"),
+        ];
+        for key in keys {
+            if let Some(v) = vdf.get(&key).filter(|v| !v.is_empty()) {
+                return Ok(Some(v.clone()));
+            }
         }
     }
 
-    Err(Arma3Error::SteamConfig {
-        message: format!("compatibility tool entry not found for appid {app_id}"),
-    })
+    Ok(None)
 }
 
-pub(crate) fn compat_tool_dir(steam_root: &Path, shortname: &str) -> Option<PathBuf> {
-    let user = steam_root.join("compatibilitytools.d").join(shortname);
-    if user.is_dir() {
-        return Some(user);
+/// Map Arma 3 to `tool` in Steam's `config/config.vdf` (`CompatToolMapping/107410`).
+///
+/// Refuses while Steam is running, since Steam rewrites the file on exit. The previous file is
+/// copied to `config/config.vdf.bak` first; everything other than the Arma mapping is kept.
+/// Returns the backup path.
+pub fn set_arma3_compat_tool(tool: &CompatTool) -> Result<PathBuf> {
+    let ctx = DetectionContext::new();
+    if process::is_steam_running(&ctx) {
+        return Err(Arma3Error::SteamRunning);
     }
-    let system = PathBuf::from("/usr/share/steam/compatibilitytools.d").join(shortname);
-    if system.is_dir() {
-        return Some(system);
+    let steam_root =
+        crate::steam::detect::detect_steam_root(&ctx).ok_or(Arma3Error::SteamNotFound)?;
+    write_compat_tool_mapping(&steam_root, ARMA3_APP_ID_STR, tool.name())
+}
+
+pub(crate) fn write_compat_tool_mapping(
+    steam_root: &Path,
+    app_id: &str,
+    tool_name: &str,
+) -> Result<PathBuf> {
+    let config_vdf_path = steam_root.join("config/config.vdf");
+    let config_txt = fs::read_to_string(&config_vdf_path).map_err(|e| Arma3Error::SteamConfig {
+        message: format!("failed reading {}: {e}", config_vdf_path.display()),
+    })?;
+
+    let mut doc = VdfDocument::parse(&config_txt)?;
+    let mapping = doc
+        .object_mut("InstallConfigStore")
+        .object_mut("Software")
+        .object_mut("Valve")
+        .object_mut("Steam")
+        .object_mut("CompatToolMapping")
+        .object_mut(app_id);
+    mapping.set_string("name", tool_name);
+    if mapping.child("config").is_none() {
+        mapping.set_string("config", "");
     }
-    None
+    // Steam uses 250 for user-selected mappings.
+    mapping.set_string("priority", "250");
+
+    let new_txt = doc.to_text();
+
+    let backup = config_vdf_path.with_file_name("config.vdf.bak");
+    fs::copy(&config_vdf_path, &backup)?;
+
+    let tmp = config_vdf_path.with_file_name("config.vdf.tmp");
+    fs::write(&tmp, new_txt)?;
+    fs::rename(&tmp, &config_vdf_path)?;
+
+    Ok(backup)
 }
 
-pub(crate) fn toolmanifest_commandline(tool_dir: &Path) -> Result<String> {
+/// Pick the tool for a Proton launch: explicit override, then Steam's mapping, then the newest
+/// official Proton.
+pub(crate) fn resolve_compat_tool(
+    ctx: &DetectionContext,
+    steam_root: &Path,
+    app_id: &str,
+    override_name: Option<&str>,
+) -> Result<CompatTool> {
+    let tools = compat_tools(ctx, steam_root);
+
+    let wanted = match override_name {
+        Some(name) => Some(name.to_string()),
+        None => mapped_compat_tool_name(steam_root, app_id)?,
+    };
+
+    match wanted {
+        Some(name) => tools
+            .into_iter()
+            .find(|t| t.matches(&name))
+            .ok_or_else(|| Arma3Error::SteamConfig {
+                message: format!("cannot find compatibility tool directory for '{name}'"),
+            }),
+        None => tools
+            .into_iter()
+            .find(|t| t.source == CompatToolSource::Official)
+            .ok_or_else(|| Arma3Error::SteamConfig {
+                message: format!(
+                    "no compatibility tool mapped for appid {app_id} and no official Proton installed"
+                ),
+            }),
+    }
+}
+
+/// Parsed `toolmanifest.vdf` of a compatibility tool or runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ToolManifest {
+    /// `manifest/commandline`, relative to the tool directory.
+    pub(crate) commandline: String,
+    /// `manifest/require_tool_appid`: tool this one must run inside (e.g. Steam Linux Runtime).
+    pub(crate) require_tool_appid: Option<String>,
+}
+
+pub(crate) fn read_toolmanifest(tool_dir: &Path) -> Result<ToolManifest> {
     let toolmanifest_path = tool_dir.join("toolmanifest.vdf");
     let tm_txt = fs::read_to_string(&toolmanifest_path).map_err(|e| Arma3Error::SteamConfig {
         message: format!("failed reading {}: {e}", toolmanifest_path.display()),
     })?;
     let tm = Vdf::parse(&tm_txt)?;
-    tm.get("manifest/commandline")
-        .cloned()
-        .ok_or_else(|| Arma3Error::SteamConfig {
-            message: format!(
-                "toolmanifest missing manifest/commandline in {}",
-                toolmanifest_path.display()
-            ),
-        })
+    let commandline =
+        tm.get("manifest/commandline")
+            .cloned()
+            .ok_or_else(|| Arma3Error::SteamConfig {
+                message: format!(
+                    "toolmanifest missing manifest/commandline in {}",
+                    toolmanifest_path.display()
+                ),
+            })?;
+    let require_tool_appid = tm
+        .get("manifest/require_tool_appid")
+        .filter(|v| !v.is_empty())
+        .cloned();
+
+    Ok(ToolManifest {
+        commandline,
+        require_tool_appid,
+    })
+}
+
+/// Resolve the directory of a tool referenced by `require_tool_appid`.
+pub(crate) fn required_tool_dir(steam_root: &Path, app_id: &str) -> Option<PathBuf> {
+    let fallback = match app_id {
+        STEAM_LINUX_RUNTIME_SNIPER_APP_ID => Some("SteamLinuxRuntime_sniper"),
+        STEAM_LINUX_RUNTIME_SOLDIER_APP_ID => Some("SteamLinuxRuntime_soldier"),
+        STEAM_LINUX_RUNTIME_SCOUT_APP_ID => Some("SteamLinuxRuntime"),
+        _ => None,
+    };
+    let libraries = library::steam_library_roots(steam_root);
+    library::app_install_dir(&libraries, app_id, fallback)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_required_tool_appid() {
+        let d = tempfile::tempdir().unwrap();
+        std::fs::write(
+            d.path().join("toolmanifest.vdf"),
+            r#"
+"manifest"
+{
+  "version" "2"
+  "commandline" "/proton %verb%"
+  "require_tool_appid" "1628350"
+  "use_sessions" "1"
+}
+"#,
+        )
+        .unwrap();
+
+        let tm = read_toolmanifest(d.path()).unwrap();
+        assert_eq!(tm.commandline, "/proton %verb%");
+        assert_eq!(tm.require_tool_appid.as_deref(), Some("1628350"));
+    }
+
+    #[test]
+    fn names_official_proton_dirs() {
+        assert_eq!(official_proton_name("Proton 9.0 (Beta)"), "proton_9");
+        assert_eq!(official_proton_name("Proton 8.0"), "proton_8");
+        assert_eq!(official_proton_name("Proton 5.13"), "proton_513");
+        assert_eq!(
+            official_proton_name("Proton - Experimental"),
+            "proton_experimental"
+        );
+        assert_eq!(official_proton_name("Proton Hotfix"), "proton_hotfix");
+    }
+
+    #[test]
+    fn lists_tools_and_falls_back_to_default_mapping() {
+        let d = tempfile::tempdir().unwrap();
+        let root = d.path();
+        for dir in ["steamapps/common/Proton 8.0", "steamapps/common/Proton 9.0"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join("toolmanifest.vdf"), "").unwrap();
+        }
+        let ge = root.join("compatibilitytools.d/GE-Proton9-20");
+        fs::create_dir_all(&ge).unwrap();
+        fs::write(ge.join("toolmanifest.vdf"), "").unwrap();
+        fs::write(
+            ge.join("compatibilitytool.vdf"),
+            r#"
+"compatibilitytools"
+{
+  "compat_tools"
+  {
+    "GE-Proton9-20" // Internal name of this tool
+    {
+      "install_path" "."
+      "display_name" "GE-Proton 9-20"
+      "from_oslist"  "windows"
+      "to_oslist"    "linux"
+    }
+  }
+}
+"#,
+        )
+        .unwrap();
+
+        let tools = compat_tools(&DetectionContext::isolated(), root);
+        let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
+        assert!(names.starts_with(&["proton_9", "proton_8", "GE-Proton9-20"]));
+        assert_eq!(tools[2].display_name(), "GE-Proton 9-20");
+        assert_eq!(tools[2].source(), CompatToolSource::User);
+
+        // No mapping at all: newest official Proton.
+        let tool =
+            resolve_compat_tool(&DetectionContext::isolated(), root, "107410", None).unwrap();
+        assert_eq!(tool.name(), "proton_9");
+
+        // Global default mapping applies when Arma has no entry of its own.
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::write(
+            root.join("config/config.vdf"),
+            r#""InstallConfigStore" { "Software" { "Valve" { "Steam" { "CompatToolMapping" { "0" { "name" "GE-Proton9-20" } } } } } }"#,
+        )
+        .unwrap();
+        let tool =
+            resolve_compat_tool(&DetectionContext::isolated(), root, "107410", None).unwrap();
+        assert_eq!(tool.name(), "GE-Proton9-20");
+
+        let tool = resolve_compat_tool(
+            &DetectionContext::isolated(),
+            root,
+            "107410",
+            Some("proton_8"),
+        )
+        .unwrap();
+        assert_eq!(tool.dir(), root.join("steamapps/common/Proton 8.0"));
+    }
+
+    #[test]
+    fn writes_mapping_and_keeps_other_settings() {
+        let d = tempfile::tempdir().unwrap();
+        let root = d.path();
+        fs::create_dir_all(root.join("config")).unwrap();
+        let original = r#"
+"InstallConfigStore"
+{
+    "Software"
+    {
+        "Valve"
+        {
+            "Steam"
+            {
+                "AutoUpdateWindowEnabled" "0"
+                "CompatToolMapping"
+                {
+                    "0"
+                    {
+                        "name" "proton_9"
+                        "config" ""
+                        "priority" "75"
+                    }
+                }
+            }
+        }
+    }
+}
+"#;
+        fs::write(root.join("config/config.vdf"), original).unwrap();
+
+        let backup = write_compat_tool_mapping(root, "107410", "GE-Proton9-20").unwrap();
+        assert_eq!(fs::read_to_string(backup).unwrap(), original);
+
+        let vdf = Vdf::parse(&fs::read_to_string(root.join("config/config.vdf")).unwrap()).unwrap();
+        let base = "InstallConfigStore/Software/Valve/Steam";
+        assert_eq!(
+            vdf.get(&format!("{base}/CompatToolMapping/107410/name"))
+                .unwrap(),
+            "GE-Proton9-20"
+        );
+        assert_eq!(
+            vdf.get(&format!("{base}/CompatToolMapping/0/name"))
+                .unwrap(),
+            "proton_9"
+        );
+        assert_eq!(
+            vdf.get(&format!("{base}/AutoUpdateWindowEnabled")).unwrap(),
+            "0"
+        );
+    }
 }
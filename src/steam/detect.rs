@@ -1,102 +1,216 @@
-use std::path::PathBuf;
+use crate::install::trace::{missing_reason, DetectionTrace};
+use crate::install::DetectionContext;
+#[cfg(target_os = "linux")]
+use crate::steam::snap;
+use crate::steam::SteamPackage;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-pub(crate) fn detect_steam_root() -> Option<PathBuf> {
-    if let Some(p) = std::env::var_os("STEAM_ROOT").map(PathBuf::from) {
-        if p.join("steamapps").is_dir() {
-            return Some(p);
-        }
+/// Flatpak Steam's data directory, relative to the home directory.
+pub(crate) const FLATPAK_STEAM_DIR: &str = ".var/app/com.valvesoftware.Steam";
+
+/// Root of the first detected Steam installation.
+pub(crate) fn detect_steam_root(ctx: &DetectionContext) -> Option<PathBuf> {
+    detect_steam_roots(ctx, &mut DetectionTrace::default())
+        .into_iter()
+        .next()
+        .map(|(root, _)| root)
+}
+
+/// Every Steam root in probe order, with how it is packaged. Paths resolving to the same
+/// directory (e.g. `~/.steam/steam` and `~/.local/share/Steam`) are listed once.
+pub(crate) fn detect_steam_roots(
+    ctx: &DetectionContext,
+    trace: &mut DetectionTrace,
+) -> Vec<(PathBuf, SteamPackage)> {
+    let mut roots = Roots {
+        ctx,
+        out: Vec::new(),
+    };
+
+    if let Some(p) = ctx.var_os("STEAM_ROOT").map(PathBuf::from) {
+        roots.probe(trace, "STEAM_ROOT", p, SteamPackage::Custom, false);
+    }
+    for p in ctx.extra_steam_roots() {
+        roots.probe(
+            trace,
+            "extra steam root",
+            p.clone(),
+            SteamPackage::Custom,
+            false,
+        );
     }
 
     #[cfg(target_os = "linux")]
-    {
-        let home = std::env::var_os("HOME").map(PathBuf::from)?;
+    if let Some(home) = ctx.home_dir() {
+        let flatpak = home.join(FLATPAK_STEAM_DIR);
+        let snap = home.join(snap::SNAP_STEAM_COMMON_DIR);
         let candidates = [
-            home.join(".steam/steam"),
-            home.join(".steam/root"),
-            home.join(".local/share/Steam"),
-            home.join(".var/app/com.valvesoftware.Steam/.steam/steam"),
-            home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+            (home.join(".steam/steam"), SteamPackage::Native),
+            (home.join(".steam/root"), SteamPackage::Native),
+            (home.join(".local/share/Steam"), SteamPackage::Native),
+            (flatpak.join(".steam/steam"), SteamPackage::Flatpak),
+            (flatpak.join(".local/share/Steam"), SteamPackage::Flatpak),
+            (snap.join(".steam/steam"), SteamPackage::Snap),
+            (snap.join(".local/share/Steam"), SteamPackage::Snap),
         ];
-
-        candidates
-            .into_iter()
-            .find(|p| p.join("config/config.vdf").is_file() || p.join("steamapps").is_dir())
+        for (p, package) in candidates {
+            roots.probe(trace, "steam root candidate", p, package, true);
+        }
     }
 
     #[cfg(target_os = "windows")]
     {
-        if let Some(p) = detect_from_registry(winreg::enums::HKEY_CURRENT_USER) {
-            return Some(p);
-        }
-        if let Some(p) = detect_from_registry(winreg::enums::HKEY_LOCAL_MACHINE) {
-            return Some(p);
+        if ctx.uses_system_state() {
+            let hives = [
+                (winreg::enums::HKEY_CURRENT_USER, "HKCU"),
+                (winreg::enums::HKEY_LOCAL_MACHINE, "HKLM"),
+            ];
+            for (root, hive) in hives {
+                for (source, p) in registry_candidates(root, hive) {
+                    roots.probe(trace, &source, p, SteamPackage::Native, false);
+                }
+            }
         }
 
         let candidates = [
-            PathBuf::from(r"C:\Program Files (x86)\Steam"),
-            PathBuf::from(r"C:\Program Files\Steam"),
+            ctx.system_path(r"C:\Program Files (x86)\Steam"),
+            ctx.system_path(r"C:\Program Files\Steam"),
         ];
+        for p in candidates {
+            roots.probe(
+                trace,
+                "steam root candidate",
+                p,
+                SteamPackage::Native,
+                false,
+            );
+        }
+    }
+
+    roots
+        .out
+        .into_iter()
+        .map(|(p, package, _)| (p, package))
+        .collect()
+}
+
+struct Roots<'a> {
+    ctx: &'a DetectionContext,
+    /// Root as probed, packaging and canonical path.
+    out: Vec<(PathBuf, SteamPackage, PathBuf)>,
+}
+
+impl Roots<'_> {
+    /// Add `path` if it has `steamapps` (or, with `config_is_enough`, `config/config.vdf`).
+    fn probe(
+        &mut self,
+        trace: &mut DetectionTrace,
+        source: &str,
+        path: PathBuf,
+        package: SteamPackage,
+        config_is_enough: bool,
+    ) {
+        let has_config = config_is_enough && path.join("config/config.vdf").is_file();
+        if !has_config && !path.join("steamapps").is_dir() {
+            let reason = if config_is_enough {
+                "no config/config.vdf or steamapps directory"
+            } else {
+                "no steamapps directory"
+            };
+            trace.reject(source, &path, missing_reason(&path, reason));
+            return;
+        }
+
+        let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if let Some((first, _, _)) = self.out.iter().find(|(_, _, c)| *c == canonical) {
+            let reason = format!("same installation as {}", first.display());
+            trace.reject(source, &path, reason);
+            return;
+        }
 
-        candidates
-            .into_iter()
-            .find(|p| p.join("steamapps").is_dir())
+        let package = self.package_of(&path, package);
+        let reason = if config_is_enough {
+            "Steam config or steamapps found"
+        } else {
+            "steamapps directory found"
+        };
+        trace.accept(source, &path, format!("{reason} ({package:?})"));
+        self.out.push((path, package, canonical));
     }
 
-    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
-    {
-        None
+    /// Roots inside the Flatpak or Snap data directory belong to that package, wherever they
+    /// came from.
+    fn package_of(&self, path: &Path, package: SteamPackage) -> SteamPackage {
+        #[cfg(target_os = "linux")]
+        if let Some(home) = self.ctx.home_dir() {
+            if path.starts_with(home.join(FLATPAK_STEAM_DIR)) {
+                return SteamPackage::Flatpak;
+            }
+            if path.starts_with(home.join(snap::SNAP_STEAM_DIR)) {
+                return SteamPackage::Snap;
+            }
+        }
+        let _ = (self.ctx, path);
+        package
     }
 }
 
 #[cfg(target_os = "windows")]
-fn detect_from_registry(root: winreg::HKEY) -> Option<PathBuf> {
+fn registry_candidates(root: winreg::HKEY, hive: &str) -> Vec<(String, PathBuf)> {
     use winreg::RegKey;
 
     let hk = RegKey::predef(root);
-    let steam = hk.open_subkey(r"Software\Valve\Steam").ok()?;
+    let Ok(steam) = hk.open_subkey(r"Software\Valve\Steam") else {
+        return Vec::new();
+    };
 
+    let mut out = Vec::new();
     if let Ok(dir) = steam.get_value::<String, _>("SteamPath") {
-        let p = PathBuf::from(dir.trim_matches('"'));
-        if p.join("steamapps").is_dir() {
-            return Some(p);
-        }
+        out.push((
+            format!(r"{hive}\Software\Valve\Steam\SteamPath"),
+            PathBuf::from(dir.trim_matches('"')),
+        ));
     }
-
     if let Ok(exe) = steam.get_value::<String, _>("SteamExe") {
-        let p = PathBuf::from(exe.trim_matches('"'));
-        if let Some(parent) = p.parent() {
-            let parent = parent.to_path_buf();
-            if parent.join("steamapps").is_dir() {
-                return Some(parent);
-            }
+        if let Some(parent) = Path::new(exe.trim_matches('"')).parent() {
+            out.push((
+                format!(r"{hive}\Software\Valve\Steam\SteamExe"),
+                parent.to_path_buf(),
+            ));
         }
     }
-
-    None
+    out
 }
 
 #[cfg(target_os = "windows")]
-pub(crate) fn detect_steam_exe() -> PathBuf {
+pub(crate) fn detect_steam_exe(ctx: &DetectionContext, steam_root: Option<&Path>) -> PathBuf {
     use winreg::enums::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
-    use winreg::RegKey;
-    use winreg::HKEY;
 
-    if let Some(p) = std::env::var_os("STEAM_EXE").map(PathBuf::from) {
+    if let Some(p) = ctx.var_os("STEAM_EXE").map(PathBuf::from) {
         if p.is_file() {
             return p;
         }
     }
 
-    if let Some(p) = detect_exe_from_registry(HKEY_CURRENT_USER) {
-        return p;
+    if let Some(p) = steam_root.map(|root| root.join("steam.exe")) {
+        if p.is_file() {
+            return p;
+        }
     }
-    if let Some(p) = detect_exe_from_registry(HKEY_LOCAL_MACHINE) {
-        return p;
+
+    if ctx.uses_system_state() {
+        if let Some(p) = detect_exe_from_registry(HKEY_CURRENT_USER) {
+            return p;
+        }
+        if let Some(p) = detect_exe_from_registry(HKEY_LOCAL_MACHINE) {
+            return p;
+        }
     }
 
     let candidates = [
-        PathBuf::from(r"C:\Program Files (x86)\Steam\steam.exe"),
-        PathBuf::from(r"C:\Program Files\Steam\steam.exe"),
+        ctx.system_path(r"C:\Program Files (x86)\Steam\steam.exe"),
+        ctx.system_path(r"C:\Program Files\Steam\steam.exe"),
     ];
     if let Some(p) = candidates.into_iter().find(|p| p.is_file()) {
         return p;
@@ -106,7 +220,9 @@ pub(crate) fn detect_steam_exe() -> PathBuf {
 }
 
 #[cfg(target_os = "windows")]
-fn detect_exe_from_registry(root: HKEY) -> Option<PathBuf> {
+fn detect_exe_from_registry(root: winreg::HKEY) -> Option<PathBuf> {
+    use winreg::RegKey;
+
     let hk = RegKey::predef(root);
     let steam = hk.open_subkey(r"Software\Valve\Steam").ok()?;
 
@@ -128,25 +244,28 @@ fn detect_exe_from_registry(root: HKEY) -> Option<PathBuf> {
 }
 
 #[cfg(target_os = "linux")]
-pub(crate) fn is_flatpak_steam() -> bool {
-    if std::env::var_os("FLATPAK_ID").is_some() {
+pub(crate) fn is_flatpak_steam(ctx: &DetectionContext) -> bool {
+    if ctx.var_os("FLATPAK_ID").is_some() {
         return true;
     }
-    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+    let Some(home) = ctx.home_dir() else {
         return false;
     };
-    home.join(".var/app/com.valvesoftware.Steam").is_dir()
+    home.join(FLATPAK_STEAM_DIR).is_dir()
 }
 
 #[cfg(target_os = "linux")]
-pub(crate) fn detect_steam_exe() -> PathBuf {
-    if let Some(p) = std::env::var_os("STEAM_EXE").map(PathBuf::from) {
+pub(crate) fn detect_steam_exe(ctx: &DetectionContext, steam_root: Option<&Path>) -> PathBuf {
+    if let Some(p) = ctx.var_os("STEAM_EXE").map(PathBuf::from) {
         if p.is_file() {
             return p;
         }
     }
 
-    if let Some(root) = detect_steam_root() {
+    let root = steam_root
+        .map(Path::to_path_buf)
+        .or_else(|| detect_steam_root(ctx));
+    if let Some(root) = root {
         let candidates = [
             root.join("steam.sh"),
             root.join("steam"),
@@ -158,12 +277,12 @@ pub(crate) fn detect_steam_exe() -> PathBuf {
     }
 
     let candidates = [
-        PathBuf::from("/usr/bin/steam"),
-        PathBuf::from("/usr/bin/steam-runtime"),
-        PathBuf::from("/usr/lib/steam/steam"),
-        PathBuf::from("/usr/lib/steam/steam.sh"),
-        PathBuf::from("/usr/lib64/steam/steam"),
-        PathBuf::from("/usr/lib64/steam/steam.sh"),
+        ctx.system_path("/usr/bin/steam"),
+        ctx.system_path("/usr/bin/steam-runtime"),
+        ctx.system_path("/usr/lib/steam/steam"),
+        ctx.system_path("/usr/lib/steam/steam.sh"),
+        ctx.system_path("/usr/lib64/steam/steam"),
+        ctx.system_path("/usr/lib64/steam/steam.sh"),
     ];
     if let Some(p) = candidates.into_iter().find(|p| p.is_file()) {
         return p;
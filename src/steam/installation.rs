@@ -0,0 +1,143 @@
+use crate::install::trace::DetectionTrace;
+use crate::install::DetectionContext;
+use crate::steam::{detect, library};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How a Steam client is installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SteamPackage {
+    /// Distribution package or Valve's installer (`~/.steam/steam`, `~/.local/share/Steam`,
+    /// or the registry / `Program Files` location on Windows).
+    Native,
+    /// Flatpak `com.valvesoftware.Steam` (`~/.var/app/com.valvesoftware.Steam`).
+    Flatpak,
+    /// Snap `steam` (`~/snap/steam/common/.local/share/Steam`).
+    Snap,
+    /// Root given by `STEAM_ROOT` or [`DetectionContext::extra_steam_root`].
+    Custom,
+}
+
+/// A Steam client installation and the libraries it manages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SteamInstallation {
+    root: PathBuf,
+    package: SteamPackage,
+    libraries: Vec<PathBuf>,
+}
+
+impl SteamInstallation {
+    /// Steam root directory (contains `config/` and `steamapps/`).
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// How this Steam client is installed.
+    pub fn package(&self) -> SteamPackage {
+        self.package
+    }
+
+    /// Library directories (each containing `steamapps/`), the root's own library first.
+    pub fn libraries(&self) -> &[PathBuf] {
+        &self.libraries
+    }
+
+    /// Installation rooted at `root`, with libraries read from its `libraryfolders.vdf`.
+    #[cfg(test)]
+    pub(crate) fn from_root(root: PathBuf, package: SteamPackage) -> Self {
+        Self {
+            libraries: library::steam_library_roots(&root),
+            root,
+            package,
+        }
+    }
+
+    /// True if `game_dir` is inside one of this installation's libraries.
+    pub(crate) fn owns(&self, game_dir: &Path) -> bool {
+        let game_dir = canonical(game_dir);
+        self.libraries
+            .iter()
+            .any(|lib| game_dir.starts_with(canonical(&lib.join("steamapps/common"))))
+    }
+}
+
+/// List every detected Steam installation (native, Flatpak, Snap, `STEAM_ROOT`) with its
+/// libraries.
+///
+/// The order matches detection priority: `STEAM_ROOT` first, then native, Flatpak and Snap.
+pub fn detect_steam_installations() -> Vec<SteamInstallation> {
+    DetectionContext::new().detect_steam_installations()
+}
+
+impl DetectionContext {
+    /// [`detect_steam_installations`] using this context.
+    pub fn detect_steam_installations(&self) -> Vec<SteamInstallation> {
+        steam_installations_traced(self, &mut DetectionTrace::default())
+    }
+}
+
+pub(crate) fn steam_installations_traced(
+    ctx: &DetectionContext,
+    trace: &mut DetectionTrace,
+) -> Vec<SteamInstallation> {
+    detect::detect_steam_roots(ctx, trace)
+        .into_iter()
+        .map(|(root, package)| SteamInstallation {
+            libraries: library::steam_library_roots_traced(&root, trace),
+            root,
+            package,
+        })
+        .collect()
+}
+
+/// The installation whose libraries contain `game_dir`, else the first one.
+pub(crate) fn owning_installation(
+    ctx: &DetectionContext,
+    game_dir: &Path,
+) -> Option<SteamInstallation> {
+    let mut installations = ctx.detect_steam_installations();
+    let index = installations
+        .iter()
+        .position(|s| s.owns(game_dir))
+        .unwrap_or(0);
+    (index < installations.len()).then(|| installations.swap_remove(index))
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn lists_native_and_flatpak_once_each() {
+        let d = tempfile::tempdir().unwrap();
+        let home = d.path();
+        let native = home.join(".local/share/Steam");
+        let flatpak = home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam");
+        fs::create_dir_all(native.join("steamapps/common/Arma 3")).unwrap();
+        fs::create_dir_all(flatpak.join("steamapps")).unwrap();
+        fs::create_dir_all(home.join(".steam")).unwrap();
+        std::os::unix::fs::symlink(&native, home.join(".steam/steam")).unwrap();
+
+        let ctx = DetectionContext::isolated().home(home);
+        let installs = ctx.detect_steam_installations();
+        let found: Vec<(&Path, SteamPackage)> =
+            installs.iter().map(|s| (s.root(), s.package())).collect();
+        assert_eq!(
+            found,
+            [
+                (home.join(".steam/steam").as_path(), SteamPackage::Native),
+                (flatpak.as_path(), SteamPackage::Flatpak),
+            ]
+        );
+
+        assert!(installs[0].owns(&native.join("steamapps/common/Arma 3")));
+        assert!(!installs[1].owns(&native.join("steamapps/common/Arma 3")));
+        let owner = owning_installation(&ctx, &flatpak.join("steamapps/common/Arma 3")).unwrap();
+        assert_eq!(owner.package(), SteamPackage::Flatpak);
+    }
+}
@@ -1,28 +1,46 @@
-use crate::steam::detect::detect_steam_root;
+use crate::install::trace::{missing_reason, DetectionTrace};
 use crate::steam::vdf::Vdf;
 use std::collections::BTreeSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-pub(crate) fn detect_steam_library_roots() -> Vec<PathBuf> {
-    let Some(root) = detect_steam_root() else {
-        return vec![];
-    };
+pub(crate) fn steam_library_roots(root: &Path) -> Vec<PathBuf> {
+    steam_library_roots_traced(root, &mut DetectionTrace::default())
+}
 
+pub(crate) fn steam_library_roots_traced(root: &Path, trace: &mut DetectionTrace) -> Vec<PathBuf> {
     let mut seen: BTreeSet<PathBuf> = BTreeSet::new();
     let mut out: Vec<PathBuf> = Vec::new();
 
-    if root.join("steamapps").is_dir() && seen.insert(root.clone()) {
-        out.push(root.clone());
+    if root.join("steamapps").is_dir() && seen.insert(root.to_path_buf()) {
+        trace.accept("steam library", root, "Steam root library");
+        out.push(root.to_path_buf());
     }
 
     let libraryfolders = root.join("steamapps/libraryfolders.vdf");
-    let Ok(txt) = fs::read_to_string(&libraryfolders) else {
-        return out;
+    let txt = match fs::read_to_string(&libraryfolders) {
+        Ok(txt) => txt,
+        Err(e) => {
+            trace.reject(
+                "libraryfolders.vdf",
+                &libraryfolders,
+                format!("unreadable: {e}"),
+            );
+            return out;
+        }
     };
-    let Ok(vdf) = Vdf::parse(&txt) else {
-        return out;
+    let vdf = match Vdf::parse(&txt) {
+        Ok(vdf) => vdf,
+        Err(e) => {
+            trace.reject(
+                "libraryfolders.vdf",
+                &libraryfolders,
+                format!("unparsable VDF: {e}"),
+            );
+            return out;
+        }
     };
+    trace.accept("libraryfolders.vdf", &libraryfolders, "parsed");
 
     for (k, v) in &vdf.kv {
         let mut parts = k.split('/');
@@ -45,10 +63,94 @@ pub(crate) fn detect_steam_library_roots() -> Vec<PathBuf> {
         }
 
         let p = PathBuf::from(v);
-        if p.join("steamapps").is_dir() && seen.insert(p.clone()) {
+        if !p.join("steamapps").is_dir() {
+            trace.reject(
+                "steam library",
+                &p,
+                missing_reason(&p, "no steamapps directory"),
+            );
+        } else if seen.insert(p.clone()) {
+            trace.accept("steam library", &p, "listed in libraryfolders.vdf");
             out.push(p);
         }
     }
 
     out
 }
+
+/// Locate the install directory of a Steam app across the given libraries.
+///
+/// Uses `steamapps/appmanifest_<appid>.acf` (`AppState/installdir`), falling back to
+/// `fallback_dir_name` under `steamapps/common` when no manifest is present.
+pub(crate) fn app_install_dir(
+    libraries: &[PathBuf],
+    app_id: &str,
+    fallback_dir_name: Option<&str>,
+) -> Option<PathBuf> {
+    for lib in libraries {
+        let steamapps = lib.join("steamapps");
+        let manifest = steamapps.join(format!("appmanifest_{app_id}.acf"));
+        let Ok(txt) = fs::read_to_string(&manifest) else {
+            continue;
+        };
+        let Ok(vdf) = Vdf::parse(&txt) else {
+            continue;
+        };
+        if let Some(dir) = vdf.get("AppState/installdir") {
+            let p = steamapps.join("common").join(dir);
+            if p.is_dir() {
+                return Some(p);
+            }
+        }
+    }
+
+    let name = fallback_dir_name?;
+    libraries
+        .iter()
+        .map(|lib| lib.join("steamapps/common").join(name))
+        .find(|p| p.is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn traces_rejected_libraries() {
+        let d = tempfile::tempdir().unwrap();
+        let root = d.path().join("Steam");
+        let extra = d.path().join("Games");
+        fs::create_dir_all(root.join("steamapps")).unwrap();
+        fs::create_dir_all(extra.join("steamapps")).unwrap();
+        let missing = d.path().join("Unmounted");
+        let vdf = root.join("steamapps/libraryfolders.vdf");
+
+        fs::write(&vdf, r#""libraryfolders" { "0" { "path" "#).unwrap();
+        let mut trace = DetectionTrace::default();
+        let roots = steam_library_roots_traced(&root, &mut trace);
+        assert_eq!(roots, [root.as_path()]);
+        let last = trace.probes().last().unwrap();
+        assert!(!last.accepted());
+        assert!(last.reason().starts_with("unparsable VDF"), "{trace}");
+
+        fs::write(
+            &vdf,
+            format!(
+                r#""libraryfolders" {{ "0" {{ "path" "{}" }} "1" {{ "path" "{}" }} }}"#,
+                extra.display(),
+                missing.display()
+            ),
+        )
+        .unwrap();
+        let mut trace = DetectionTrace::default();
+        let roots = steam_library_roots_traced(&root, &mut trace);
+        assert_eq!(roots, [root.as_path(), extra.as_path()]);
+        let rejected: Vec<(&Path, &str)> = trace
+            .probes()
+            .iter()
+            .filter(|p| !p.accepted())
+            .map(|p| (p.path(), p.reason()))
+            .collect();
+        assert_eq!(rejected, [(missing.as_path(), "does not exist")]);
+    }
+}
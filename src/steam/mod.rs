@@ -1,14 +1,57 @@
+pub(crate) mod appinfo;
 pub(crate) mod compat;
 pub(crate) mod detect;
+pub(crate) mod installation;
 pub(crate) mod library;
+pub(crate) mod process;
 #[cfg(target_os = "linux")]
 pub(crate) mod runtime;
+pub(crate) mod shortcuts;
+#[cfg(target_os = "linux")]
+pub(crate) mod snap;
+pub(crate) mod users;
 pub(crate) mod vdf;
+pub(crate) mod vdf_binary;
+
+pub use appinfo::{detect_arma3_app_info, parse_appinfo, read_app_info, AppInfo};
+pub use compat::{detect_compat_tools, set_arma3_compat_tool, CompatTool, CompatToolSource};
+pub use installation::{detect_steam_installations, SteamInstallation, SteamPackage};
+pub use shortcuts::{add_steam_shortcut, detect_steam_account_ids, SteamShortcut};
+pub use users::{
+    detect_active_steam_account, detect_steam_accounts, read_arma3_launch_options, SteamAccount,
+};
+pub use vdf::{VdfDocument, VdfNode, VdfValue};
+pub use vdf_binary::BinaryType;
 
+pub(crate) const ARMA3_APP_ID: u32 = 107410;
 pub(crate) const ARMA3_APP_ID_STR: &str = "107410";
+pub(crate) const STEAM_LINUX_RUNTIME_SCOUT_APP_ID: &str = "1070560";
+pub(crate) const STEAM_LINUX_RUNTIME_SOLDIER_APP_ID: &str = "1391110";
+pub(crate) const STEAM_LINUX_RUNTIME_SNIPER_APP_ID: &str = "1628350";
 pub(crate) const STEAM_ARG_APPLAUNCH: &str = "-applaunch";
 pub(crate) const STEAM_ARG_NO_LAUNCHER: &str = "-nolauncher";
+pub(crate) const STEAM_ARG_SILENT: &str = "-silent";
+pub(crate) const FLATPAK_STEAM_APP_ID: &str = "com.valvesoftware.Steam";
 
 pub(crate) const ENV_STEAM_GAME_ID: &str = "SteamGameId";
+pub(crate) const ENV_STEAM_APP_ID: &str = "SteamAppId";
+pub(crate) const ENV_STEAM_COMPAT_APP_ID: &str = "STEAM_COMPAT_APP_ID";
 pub(crate) const ENV_STEAM_COMPAT_DATA_PATH: &str = "STEAM_COMPAT_DATA_PATH";
+pub(crate) const ENV_STEAM_COMPAT_CLIENT_INSTALL_PATH: &str = "STEAM_COMPAT_CLIENT_INSTALL_PATH";
+pub(crate) const ENV_STEAM_COMPAT_INSTALL_PATH: &str = "STEAM_COMPAT_INSTALL_PATH";
+pub(crate) const ENV_STEAM_COMPAT_LIBRARY_PATHS: &str = "STEAM_COMPAT_LIBRARY_PATHS";
+pub(crate) const ENV_STEAM_COMPAT_SHADER_PATH: &str = "STEAM_COMPAT_SHADER_PATH";
+pub(crate) const ENV_STEAM_COMPAT_TOOL_PATHS: &str = "STEAM_COMPAT_TOOL_PATHS";
 pub(crate) const ENV_PROTON_NO_ESYNC: &str = "PROTON_NO_ESYNC";
+pub(crate) const ENV_PROTON_NO_FSYNC: &str = "PROTON_NO_FSYNC";
+pub(crate) const ENV_PROTON_USE_WINED3D: &str = "PROTON_USE_WINED3D";
+pub(crate) const ENV_PROTON_ENABLE_NVAPI: &str = "PROTON_ENABLE_NVAPI";
+pub(crate) const ENV_PROTON_LOG: &str = "PROTON_LOG";
+pub(crate) const ENV_PROTON_LOG_DIR: &str = "PROTON_LOG_DIR";
+pub(crate) const ENV_PROTON_FORCE_LARGE_ADDRESS_AWARE: &str = "PROTON_FORCE_LARGE_ADDRESS_AWARE";
+pub(crate) const ENV_DXVK_ASYNC: &str = "DXVK_ASYNC";
+pub(crate) const ENV_DXVK_HUD: &str = "DXVK_HUD";
+pub(crate) const ENV_WINEDLLOVERRIDES: &str = "WINEDLLOVERRIDES";
+
+pub(crate) const VERB_RUN: &str = "run";
+pub(crate) const VERB_WAIT_FOR_EXIT_AND_RUN: &str = "waitforexitandrun";
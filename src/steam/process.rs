@@ -0,0 +1,181 @@
+use crate::install::DetectionContext;
+#[cfg(target_os = "linux")]
+use crate::steam::vdf::VdfDocument;
+use crate::steam::SteamInstallation;
+#[cfg(target_os = "linux")]
+use crate::steam::SteamPackage;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+
+/// Steam's per-user state directories (`steam.pid`, `steam.pipe`, `registry.vdf`).
+#[cfg(target_os = "linux")]
+fn dot_steam_dirs(ctx: &DetectionContext) -> Vec<PathBuf> {
+    [
+        SteamPackage::Native,
+        SteamPackage::Flatpak,
+        SteamPackage::Snap,
+    ]
+    .into_iter()
+    .filter_map(|package| dot_steam_dir(ctx, package))
+    .collect()
+}
+
+/// State directory of a Steam client installed as `package`.
+#[cfg(target_os = "linux")]
+fn dot_steam_dir(ctx: &DetectionContext, package: SteamPackage) -> Option<PathBuf> {
+    let home = ctx.home_dir()?;
+    Some(match package {
+        SteamPackage::Native | SteamPackage::Custom => home.join(".steam"),
+        SteamPackage::Flatpak => home.join(".var/app/com.valvesoftware.Steam/.steam"),
+        SteamPackage::Snap => home.join("snap/steam/common/.steam"),
+    })
+}
+
+/// True if a Steam client process appears to be running for the current user.
+///
+/// Checks the `steam.pid` files in `ctx`'s home directory, then scans for a `steam` process
+/// owned by the current user.
+#[cfg(target_os = "linux")]
+pub(crate) fn is_steam_running(ctx: &DetectionContext) -> bool {
+    for dir in dot_steam_dirs(ctx) {
+        let Ok(txt) = fs::read_to_string(dir.join("steam.pid")) else {
+            continue;
+        };
+        if let Ok(pid) = txt.trim().parse::<u32>() {
+            if is_steam_pid(pid) {
+                return true;
+            }
+        }
+    }
+    user_steam_process_exists()
+}
+
+/// True once the Steam client `steam` accepts commands such as `-applaunch`.
+///
+/// Steam creates its IPC pipe once the client has finished starting up, and records its pid
+/// under `ActiveProcess` in `registry.vdf`. Flatpak and Snap Steam record a pid from the
+/// sandbox's PID namespace, so for them any `steam` process of the current user counts.
+#[cfg(target_os = "linux")]
+pub(crate) fn is_steam_ready(ctx: &DetectionContext, steam: &SteamInstallation) -> bool {
+    let Some(dir) = dot_steam_dir(ctx, steam.package()) else {
+        return false;
+    };
+    if !dir.join("steam.pipe").exists() {
+        return false;
+    }
+    if matches!(steam.package(), SteamPackage::Flatpak | SteamPackage::Snap) {
+        return user_steam_process_exists();
+    }
+    let Ok(txt) = fs::read_to_string(dir.join("registry.vdf")) else {
+        return false;
+    };
+    VdfDocument::parse(&txt)
+        .ok()
+        .and_then(|doc| {
+            doc.get_str("Registry/HKCU/Software/Valve/Steam/ActiveProcess/pid")?
+                .parse::<u32>()
+                .ok()
+        })
+        .is_some_and(|pid| pid != 0 && is_steam_pid(pid))
+}
+
+/// True if a `steam` process owned by the current user exists.
+#[cfg(target_os = "linux")]
+fn user_steam_process_exists() -> bool {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return false;
+    };
+    let uid = proc_uid("self");
+    entries
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+        .any(|pid| is_steam_pid(pid) && uid.is_some() && proc_uid(&pid.to_string()) == uid)
+}
+
+#[cfg(target_os = "linux")]
+fn is_steam_pid(pid: u32) -> bool {
+    fs::read_to_string(format!("/proc/{pid}/comm"))
+        .map(|comm| comm.trim() == "steam")
+        .unwrap_or(false)
+}
+
+/// Effective user ID of a process (`/proc/<pid>` is owned by it).
+#[cfg(target_os = "linux")]
+fn proc_uid(pid: &str) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::metadata(format!("/proc/{pid}")).ok().map(|m| m.uid())
+}
+
+/// True if a Steam client process appears to be running for the current user.
+///
+/// Steam publishes its pid under `HKCU\Software\Valve\Steam\ActiveProcess` while running.
+#[cfg(target_os = "windows")]
+pub(crate) fn is_steam_running(_ctx: &DetectionContext) -> bool {
+    use winreg::enums::HKEY_CURRENT_USER;
+    use winreg::RegKey;
+
+    RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey(r"Software\Valve\Steam\ActiveProcess")
+        .and_then(|k| k.get_value::<u32, _>("pid"))
+        .map(|pid| pid != 0)
+        .unwrap_or(false)
+}
+
+/// True once the running Steam client accepts commands such as `-applaunch`.
+///
+/// Steam sets `ActiveProcess\pid` once the client has started.
+#[cfg(target_os = "windows")]
+pub(crate) fn is_steam_ready(ctx: &DetectionContext, _steam: &SteamInstallation) -> bool {
+    is_steam_running(ctx)
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_state_from_context_home() {
+        let ctx = DetectionContext::isolated().home("/home/u");
+        assert_eq!(dot_steam_dirs(&ctx)[0], PathBuf::from("/home/u/.steam"));
+        assert!(dot_steam_dirs(&DetectionContext::isolated()).is_empty());
+        assert_eq!(proc_uid("self"), proc_uid(&std::process::id().to_string()));
+    }
+
+    #[test]
+    fn flatpak_steam_is_ready_despite_sandbox_pid() {
+        let d = tempfile::tempdir().unwrap();
+        let home = d.path();
+        let registry = r#""Registry" { "HKCU" { "Software" { "Valve" { "Steam" { "ActiveProcess" { "pid" "2" } } } } } }"#;
+        for dir in [".steam", ".var/app/com.valvesoftware.Steam/.steam"] {
+            let dir = home.join(dir);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("steam.pipe"), "").unwrap();
+            fs::write(dir.join("registry.vdf"), registry).unwrap();
+        }
+        let ctx = DetectionContext::isolated().home(home);
+        let flatpak = SteamInstallation::from_root(
+            home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+            SteamPackage::Flatpak,
+        );
+        let native =
+            SteamInstallation::from_root(home.join(".local/share/Steam"), SteamPackage::Native);
+
+        // The registry pid belongs to the sandbox; the host sees the client as another pid.
+        let fake_steam = home.join("steam");
+        fs::copy("/bin/sleep", &fake_steam).unwrap();
+        let mut child = std::process::Command::new(&fake_steam)
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let ready = is_steam_ready(&ctx, &flatpak);
+        let native_ready = is_steam_ready(&ctx, &native);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert!(ready);
+        assert!(!native_ready);
+    }
+}
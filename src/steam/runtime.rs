@@ -1,25 +1,39 @@
-use crate::steam::detect::detect_steam_root;
-use std::path::PathBuf;
+use crate::install::DetectionContext;
+use std::path::{Path, PathBuf};
 
-pub(crate) fn linux_overlay_so() -> Option<PathBuf> {
-    let steam = detect_steam_root()?;
-    let a = steam.join("ubuntu12_64/gameoverlayrenderer.so");
+pub(crate) fn linux_overlay_so(steam_root: &Path) -> Option<PathBuf> {
+    let a = steam_root.join("ubuntu12_64/gameoverlayrenderer.so");
     if a.is_file() {
         return Some(a);
     }
-    let b = steam.join("linux64/gameoverlayrenderer.so");
+    let b = steam_root.join("linux64/gameoverlayrenderer.so");
     if b.is_file() {
         return Some(b);
     }
     None
 }
 
-pub(crate) fn steam_runtime_runsh() -> Option<PathBuf> {
-    let steam = detect_steam_root()?;
-    let p = steam.join("ubuntu12_32/steam-runtime/run.sh");
+pub(crate) fn steam_runtime_runsh(steam_root: &Path) -> Option<PathBuf> {
+    let p = steam_root.join("ubuntu12_32/steam-runtime/run.sh");
     if p.is_file() {
         Some(p)
     } else {
         None
     }
 }
+
+pub(crate) fn missing_libpng12(ctx: &DetectionContext) -> bool {
+    let candidates = [
+        "/usr/lib/libpng12.so.0",
+        "/usr/lib/libpng12.so",
+        "/usr/lib64/libpng12.so.0",
+        "/usr/lib64/libpng12.so",
+        "/lib/libpng12.so.0",
+        "/lib/libpng12.so",
+        "/lib64/libpng12.so.0",
+        "/lib64/libpng12.so",
+        "/usr/lib/x86_64-linux-gnu/libpng12.so.0",
+        "/usr/lib/x86_64-linux-gnu/libpng12.so",
+    ];
+    !candidates.iter().any(|p| ctx.system_path(p).is_file())
+}
@@ -0,0 +1,315 @@
+use crate::error::{Arma3Error, Result};
+use crate::install::DetectionContext;
+use crate::steam::vdf::{VdfDocument, VdfNode};
+use crate::steam::{process, BinaryType};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SHORTCUTS_FILE: &str = "shortcuts.vdf";
+
+/// A non-Steam game entry for `userdata/<account id>/config/shortcuts.vdf`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SteamShortcut {
+    app_name: String,
+    exe: PathBuf,
+    start_dir: Option<PathBuf>,
+    launch_options: String,
+    icon: Option<PathBuf>,
+    tags: Vec<String>,
+}
+
+impl SteamShortcut {
+    /// Create a shortcut named `app_name` (as shown in the library) that runs `exe`.
+    pub fn new(app_name: impl Into<String>, exe: impl Into<PathBuf>) -> Self {
+        Self {
+            app_name: app_name.into(),
+            exe: exe.into(),
+            start_dir: None,
+            launch_options: String::new(),
+            icon: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Working directory (defaults to the executable's directory).
+    pub fn start_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.start_dir = Some(dir.into());
+        self
+    }
+
+    /// Arguments passed to the executable, e.g. `--preset "Our Unit"`.
+    pub fn launch_options(mut self, options: impl Into<String>) -> Self {
+        self.launch_options = options.into();
+        self
+    }
+
+    /// Icon file shown in the library.
+    pub fn icon(mut self, icon: impl Into<PathBuf>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Add a library collection tag.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Name shown in the library.
+    pub fn app_name(&self) -> &str {
+        &self.app_name
+    }
+
+    /// Executable the shortcut runs.
+    pub fn exe(&self) -> &Path {
+        &self.exe
+    }
+
+    /// Shortcut app ID, as Steam derives it from the quoted executable and the name.
+    ///
+    /// This is the ID used for `userdata/<id>/config/grid` artwork and compat tool mappings.
+    pub fn app_id(&self) -> u32 {
+        shortcut_app_id(&self.quoted_exe(), &self.app_name)
+    }
+
+    fn quoted_exe(&self) -> String {
+        quote(&self.exe)
+    }
+
+    fn quoted_start_dir(&self) -> String {
+        match &self.start_dir {
+            Some(dir) => quote(dir),
+            None => quote(self.exe.parent().unwrap_or(Path::new(""))),
+        }
+    }
+
+    /// Write our fields into `entry`, keeping fields Steam added (play time, hidden, ...).
+    fn apply_to(&self, entry: &mut VdfNode) {
+        set_typed(
+            entry,
+            "appid",
+            (self.app_id() as i32).to_string(),
+            BinaryType::Int32,
+        );
+        entry.set_string("AppName", self.app_name.as_str());
+        entry.set_string("Exe", self.quoted_exe());
+        entry.set_string("StartDir", self.quoted_start_dir());
+        entry.set_string(
+            "icon",
+            self.icon
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+        );
+        entry.set_string("LaunchOptions", self.launch_options.as_str());
+
+        for (key, default) in [
+            ("IsHidden", "0"),
+            ("AllowDesktopConfig", "1"),
+            ("AllowOverlay", "1"),
+            ("OpenVR", "0"),
+            ("LastPlayTime", "0"),
+        ] {
+            if entry.child(key).is_none() {
+                set_typed(entry, key, default.to_string(), BinaryType::Int32);
+            }
+        }
+
+        let tags = entry.object_mut("tags");
+        for tag in &self.tags {
+            if !tags.children().iter().any(|t| t.as_str() == Some(tag)) {
+                let index = tags.children().len().to_string();
+                tags.push(VdfNode::string(index, tag.as_str()));
+            }
+        }
+    }
+}
+
+/// Add (or update) a non-Steam shortcut for one Steam account.
+///
+/// `account_id` is the `userdata` directory name (the 32-bit account ID). Other shortcuts are
+/// kept as they are; an existing entry with the same app ID is updated in place. Fails with
+/// [`Arma3Error::SteamRunning`] while Steam is running, since Steam rewrites the file on exit.
+/// Returns the shortcut's app ID.
+pub fn add_steam_shortcut(account_id: u32, shortcut: &SteamShortcut) -> Result<u32> {
+    let ctx = DetectionContext::new();
+    if process::is_steam_running(&ctx) {
+        return Err(Arma3Error::SteamRunning);
+    }
+    let steam_root =
+        crate::steam::detect::detect_steam_root(&ctx).ok_or(Arma3Error::SteamNotFound)?;
+    write_shortcut(&steam_root, account_id, shortcut)
+}
+
+/// Account IDs with a `userdata` directory in the detected Steam installation.
+pub fn detect_steam_account_ids() -> Vec<u32> {
+    match crate::steam::detect::detect_steam_root(&DetectionContext::new()) {
+        Some(root) => account_ids(&root),
+        None => Vec::new(),
+    }
+}
+
+pub(crate) fn account_ids(steam_root: &Path) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir(steam_root.join("userdata")) else {
+        return Vec::new();
+    };
+    let mut ids: Vec<u32> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().to_str()?.parse().ok())
+        .filter(|id| *id != 0)
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+pub(crate) fn write_shortcut(
+    steam_root: &Path,
+    account_id: u32,
+    shortcut: &SteamShortcut,
+) -> Result<u32> {
+    let config_dir = steam_root.join(format!("userdata/{account_id}/config"));
+    let path = config_dir.join(SHORTCUTS_FILE);
+
+    let mut doc = match fs::read(&path) {
+        Ok(bytes) => VdfDocument::parse_binary(&bytes)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => VdfDocument::new(),
+        Err(e) => {
+            return Err(Arma3Error::SteamConfig {
+                message: format!("failed reading {}: {e}", path.display()),
+            })
+        }
+    };
+
+    let app_id = shortcut.app_id();
+    let list = doc.object_mut("shortcuts");
+    let existing = list.children().iter().position(|e| {
+        e.get_str("appid")
+            .and_then(|id| id.parse::<i32>().ok())
+            .is_some_and(|id| id as u32 == app_id)
+    });
+    match existing {
+        Some(i) => shortcut.apply_to(&mut list.children_mut()[i]),
+        None => {
+            let index = list
+                .children()
+                .iter()
+                .filter_map(|e| e.key().parse::<usize>().ok())
+                .max()
+                .map_or(0, |i| i + 1);
+            let mut entry = VdfNode::object(index.to_string());
+            shortcut.apply_to(&mut entry);
+            list.push(entry);
+        }
+    }
+
+    let bytes = doc.to_binary()?;
+
+    fs::create_dir_all(&config_dir)?;
+    if path.is_file() {
+        fs::copy(&path, path.with_file_name("shortcuts.vdf.bak"))?;
+    }
+    let tmp = path.with_file_name("shortcuts.vdf.tmp");
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, &path)?;
+
+    Ok(app_id)
+}
+
+/// Steam's shortcut ID: CRC-32 of exe + name with the top bit set.
+fn shortcut_app_id(quoted_exe: &str, app_name: &str) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for b in quoted_exe.bytes().chain(app_name.bytes()) {
+        crc ^= u32::from(b);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc | 0x8000_0000
+}
+
+fn quote(path: &Path) -> String {
+    format!("\"{}\"", path.display())
+}
+
+fn set_typed(entry: &mut VdfNode, key: &str, value: String, binary_type: BinaryType) {
+    entry.set_string(key, value);
+    if let Some(node) = entry
+        .children_mut()
+        .iter_mut()
+        .find(|c| c.key().eq_ignore_ascii_case(key))
+    {
+        node.set_binary_type(Some(binary_type));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_steam_shortcut_ids() {
+        // CRC-32 check value for "123456789".
+        assert_eq!(shortcut_app_id("12345", "6789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn adds_shortcut_and_keeps_existing_entries() {
+        let d = tempfile::tempdir().unwrap();
+        let config = d.path().join("userdata/1234/config");
+        fs::create_dir_all(&config).unwrap();
+
+        let mut existing = Vec::new();
+        existing.extend_from_slice(b"\x00shortcuts\x00\x000\x00");
+        existing.extend_from_slice(b"\x02appid\x00");
+        existing.extend_from_slice(&(-5_i32).to_le_bytes());
+        existing.extend_from_slice(b"\x01AppName\x00Other Game\x00");
+        existing.extend_from_slice(b"\x02LastPlayTime\x00");
+        existing.extend_from_slice(&1_700_000_000_i32.to_le_bytes());
+        existing.extend_from_slice(b"\x08\x08\x08");
+        fs::write(config.join("shortcuts.vdf"), &existing).unwrap();
+
+        let shortcut = SteamShortcut::new("Arma 3 \u{2013} Our Unit Modpack", "/opt/launcher")
+            .launch_options("--preset unit")
+            .tag("Arma");
+        let app_id = write_shortcut(d.path(), 1234, &shortcut).unwrap();
+        assert_eq!(app_id, shortcut.app_id());
+        assert!(app_id & 0x8000_0000 != 0);
+
+        // A second write updates the same entry instead of adding a duplicate.
+        write_shortcut(
+            d.path(),
+            1234,
+            &shortcut.clone().launch_options("--preset b"),
+        )
+        .unwrap();
+
+        let bytes = fs::read(config.join("shortcuts.vdf")).unwrap();
+        let doc = VdfDocument::parse_binary(&bytes).unwrap();
+        let list = doc.get("shortcuts").unwrap().children();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].get_str("AppName"), Some("Other Game"));
+        assert_eq!(list[0].get_str("LastPlayTime"), Some("1700000000"));
+
+        let ours = &list[1];
+        assert_eq!(ours.key(), "1");
+        assert_eq!(
+            ours.get_str("appid"),
+            Some((app_id as i32).to_string().as_str())
+        );
+        assert_eq!(ours.get_str("Exe"), Some("\"/opt/launcher\""));
+        assert_eq!(ours.get_str("StartDir"), Some("\"/opt\""));
+        assert_eq!(ours.get_str("LaunchOptions"), Some("--preset b"));
+        assert_eq!(ours.get_str("tags/0"), Some("Arma"));
+        assert_eq!(
+            ours.child("appid").unwrap().binary_type(),
+            Some(BinaryType::Int32)
+        );
+        assert!(config.join("shortcuts.vdf.bak").is_file());
+        assert_eq!(account_ids(d.path()), [1234]);
+    }
+}
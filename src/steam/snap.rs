@@ -0,0 +1,65 @@
+use crate::install::DetectionContext;
+use std::fs;
+use std::path::{Component, Path};
+
+/// Snap Steam's per-user data directory, relative to the home directory.
+pub(crate) const SNAP_STEAM_DIR: &str = "snap/steam";
+/// Where Snap Steam keeps its Steam root (`$SNAP_USER_COMMON`), relative to the home directory.
+pub(crate) const SNAP_STEAM_COMMON_DIR: &str = "snap/steam/common";
+/// Directories reachable only through the snap's `removable-media` interface.
+const REMOVABLE_MEDIA_DIRS: [&str; 3] = ["/media", "/mnt", "/run/media"];
+
+/// Why a game started by Snap Steam cannot read `path`, if it cannot.
+///
+/// The confined Steam sees non-hidden files in the home directory (`home` interface) and its
+/// own snap directory; `/media` and `/mnt` need `removable-media`, everything else is hidden.
+pub(crate) fn snap_access_problem(ctx: &DetectionContext, path: &Path) -> Option<String> {
+    let home = ctx.home_dir()?;
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let home = fs::canonicalize(&home).unwrap_or(home);
+
+    if path.starts_with(home.join(SNAP_STEAM_DIR)) {
+        return None;
+    }
+    if let Ok(rel) = path.strip_prefix(&home) {
+        let hidden = rel.components().any(|c| match c {
+            Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+            _ => false,
+        });
+        return hidden.then(|| {
+            "hidden directories in the home directory are not visible to Snap Steam".to_string()
+        });
+    }
+    if REMOVABLE_MEDIA_DIRS.iter().any(|dir| path.starts_with(dir)) {
+        return Some(
+            "only visible to Snap Steam after `snap connect steam:removable-media`".to_string(),
+        );
+    }
+    Some("outside the home directory, which Snap Steam cannot access".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_paths_outside_snap_confinement() {
+        let ctx = DetectionContext::isolated().home("/home/u");
+        let problem = |p: &str| snap_access_problem(&ctx, Path::new(p));
+
+        assert_eq!(problem("/home/u/arma/@cba"), None);
+        assert_eq!(
+            problem(
+                "/home/u/snap/steam/common/.local/share/Steam/steamapps/workshop/content/107410/1"
+            ),
+            None
+        );
+        assert!(problem("/home/u/.local/share/arma/@cba")
+            .unwrap()
+            .contains("hidden"));
+        assert!(problem("/mnt/games/@cba")
+            .unwrap()
+            .contains("removable-media"));
+        assert!(problem("/opt/mods/@cba").unwrap().contains("outside"));
+    }
+}
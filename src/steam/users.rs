@@ -0,0 +1,214 @@
+use crate::error::{Arma3Error, Result};
+use crate::install::DetectionContext;
+use crate::steam::vdf::VdfDocument;
+use crate::steam::ARMA3_APP_ID_STR;
+use std::fs;
+use std::path::Path;
+
+const LOGINUSERS_REL: &str = "config/loginusers.vdf";
+/// Lower 32 bits of a SteamID64 hold the account ID used for `userdata/<id>`.
+const ACCOUNT_ID_MASK: u64 = 0xFFFF_FFFF;
+
+/// A Steam account that has signed in on this machine (from `config/loginusers.vdf`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SteamAccount {
+    steam_id: u64,
+    account_name: String,
+    persona_name: String,
+    most_recent: bool,
+    timestamp: u64,
+}
+
+impl SteamAccount {
+    /// 64-bit SteamID.
+    pub fn steam_id(&self) -> u64 {
+        self.steam_id
+    }
+
+    /// 32-bit account ID (the `userdata/<id>` directory name).
+    pub fn account_id(&self) -> u32 {
+        (self.steam_id & ACCOUNT_ID_MASK) as u32
+    }
+
+    /// Login name.
+    pub fn account_name(&self) -> &str {
+        &self.account_name
+    }
+
+    /// Display (persona) name.
+    pub fn persona_name(&self) -> &str {
+        &self.persona_name
+    }
+
+    /// True if Steam marked this as the most recently signed-in account.
+    pub fn is_most_recent(&self) -> bool {
+        self.most_recent
+    }
+
+    /// Unix time of the last sign-in.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+/// Accounts listed in the detected Steam installation's `config/loginusers.vdf`.
+pub fn detect_steam_accounts() -> Result<Vec<SteamAccount>> {
+    let steam_root = crate::steam::detect::detect_steam_root(&DetectionContext::new())
+        .ok_or(Arma3Error::SteamNotFound)?;
+    accounts(&steam_root)
+}
+
+/// The account Steam signs in with: the one marked most recent, else the newest sign-in.
+pub fn detect_active_steam_account() -> Result<Option<SteamAccount>> {
+    let steam_root = crate::steam::detect::detect_steam_root(&DetectionContext::new())
+        .ok_or(Arma3Error::SteamNotFound)?;
+    active_account(&steam_root)
+}
+
+/// Arma 3 launch options the given account set in Steam (game Properties > Launch Options).
+///
+/// Returns `None` if none are set.
+pub fn read_arma3_launch_options(account_id: u32) -> Result<Option<String>> {
+    let steam_root = crate::steam::detect::detect_steam_root(&DetectionContext::new())
+        .ok_or(Arma3Error::SteamNotFound)?;
+    launch_options(&steam_root, account_id, ARMA3_APP_ID_STR)
+}
+
+pub(crate) fn accounts(steam_root: &Path) -> Result<Vec<SteamAccount>> {
+    let path = steam_root.join(LOGINUSERS_REL);
+    let txt = match fs::read_to_string(&path) {
+        Ok(txt) => txt,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(Arma3Error::SteamConfig {
+                message: format!("failed reading {}: {e}", path.display()),
+            })
+        }
+    };
+
+    let doc = VdfDocument::parse(&txt)?;
+    let Some(users) = doc.get("users") else {
+        return Ok(Vec::new());
+    };
+    Ok(users
+        .children()
+        .iter()
+        .filter_map(|u| {
+            Some(SteamAccount {
+                steam_id: u.key().parse().ok()?,
+                account_name: u.get_str("AccountName").unwrap_or_default().to_string(),
+                persona_name: u.get_str("PersonaName").unwrap_or_default().to_string(),
+                most_recent: u.get_str("MostRecent") == Some("1"),
+                timestamp: u
+                    .get_str("Timestamp")
+                    .and_then(|t| t.parse().ok())
+                    .unwrap_or(0),
+            })
+        })
+        .collect())
+}
+
+pub(crate) fn active_account(steam_root: &Path) -> Result<Option<SteamAccount>> {
+    let accounts = accounts(steam_root)?;
+    let most_recent = accounts.iter().find(|a| a.most_recent).cloned();
+    Ok(most_recent.or_else(|| accounts.into_iter().max_by_key(|a| a.timestamp)))
+}
+
+pub(crate) fn launch_options(
+    steam_root: &Path,
+    account_id: u32,
+    app_id: &str,
+) -> Result<Option<String>> {
+    let path = steam_root.join(format!("userdata/{account_id}/config/localconfig.vdf"));
+    let txt = match fs::read_to_string(&path) {
+        Ok(txt) => txt,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(Arma3Error::SteamConfig {
+                message: format!("failed reading {}: {e}", path.display()),
+            })
+        }
+    };
+
+    let doc = VdfDocument::parse(&txt)?;
+    Ok(doc
+        .get(&format!(
+            "UserLocalConfigStore/Software/Valve/Steam/apps/{app_id}/LaunchOptions"
+        ))
+        .and_then(|n| n.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_accounts_and_launch_options() {
+        let d = tempfile::tempdir().unwrap();
+        fs::create_dir_all(d.path().join("config")).unwrap();
+        fs::write(
+            d.path().join(LOGINUSERS_REL),
+            r#""users"
+{
+	"76561197960287930"
+	{
+		"AccountName"		"old"
+		"PersonaName"		"Old Account"
+		"MostRecent"		"0"
+		"Timestamp"		"1600000000"
+	}
+	"76561198000000001"
+	{
+		"AccountName"		"player"
+		"PersonaName"		"Cpt. Miller"
+		"mostrecent"		"1"
+		"Timestamp"		"1500000000"
+	}
+}
+"#,
+        )
+        .unwrap();
+
+        let active = active_account(d.path()).unwrap().unwrap();
+        assert_eq!(active.persona_name(), "Cpt. Miller");
+        assert_eq!(active.account_id(), 39_734_273);
+        assert_eq!(accounts(d.path()).unwrap().len(), 2);
+
+        let config = d.path().join("userdata/39734273/config");
+        fs::create_dir_all(&config).unwrap();
+        fs::write(
+            config.join("localconfig.vdf"),
+            r#""UserLocalConfigStore"
+{
+	"Software"
+	{
+		"Valve"
+		{
+			"Steam"
+			{
+				"Apps"
+				{
+					"107410"
+					{
+						"LastPlayed"		"1700000000"
+						"LaunchOptions"		"-noSplash -world=empty"
+					}
+				}
+			}
+		}
+	}
+}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            launch_options(d.path(), 39_734_273, ARMA3_APP_ID_STR).unwrap(),
+            Some("-noSplash -world=empty".to_string())
+        );
+        assert_eq!(launch_options(d.path(), 1, ARMA3_APP_ID_STR).unwrap(), None);
+    }
+}
@@ -1,13 +1,12 @@
 use crate::error::{Arma3Error, Result};
+use crate::steam::vdf_binary::{self, BinaryType};
 use std::collections::BTreeMap;
+use std::fmt;
 
-/// Minimal VDF parser suitable for Steam `config.vdf`, `libraryfolders.vdf`, and `toolmanifest.vdf`.
+/// Flattened view of a VDF file, for simple lookups.
 ///
-/// This is a tolerant parser:
-/// - keys/values are quoted strings
-/// - nesting is `{ ... }`
-///
-/// Output keys are stored as `Path/To/Key`
+/// Output keys are stored as `Path/To/Key`. Duplicate keys keep the last value, and nodes whose
+/// conditional (e.g. `[$WIN32]`) does not apply to the current platform are skipped.
 #[derive(Debug, Default, Clone)]
 pub(crate) struct Vdf {
     pub(crate) kv: BTreeMap<String, String>,
@@ -15,9 +14,9 @@ pub(crate) struct Vdf {
 
 impl Vdf {
     pub(crate) fn parse(text: &str) -> Result<Self> {
-        let mut p = Parser::new(text);
-        p.parse()?;
-        Ok(Self { kv: p.out })
+        Ok(Self {
+            kv: VdfDocument::parse(text)?.flatten(),
+        })
     }
 
     pub(crate) fn get(&self, key: &str) -> Option<&String> {
@@ -25,132 +24,695 @@ impl Vdf {
     }
 }
 
-struct Parser<'a> {
-    s: &'a [u8],
-    i: usize,
-    out: BTreeMap<String, String>,
-    stack: Vec<String>,
-    pending_key: Option<String>,
+/// A text VDF (Valve KeyValues) document, such as Steam's `config.vdf` or `libraryfolders.vdf`.
+///
+/// The document keeps key order, duplicate keys, `//` comments, bare (unquoted) tokens, and
+/// `[$PLATFORM]` conditionals. Serializing a parsed document reproduces the input byte-for-byte;
+/// edited or added nodes are written the way Steam formats them (tab indentation, quoted tokens).
+///
+/// Lookups by key are ASCII case-insensitive, like Steam's own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VdfDocument {
+    nodes: Vec<VdfNode>,
+    trailing: String,
 }
 
-impl<'a> Parser<'a> {
-    fn new(text: &'a str) -> Self {
+impl Default for VdfDocument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VdfDocument {
+    /// Create an empty document.
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            trailing: "\n".to_string(),
+        }
+    }
+
+    /// Create a document from top-level nodes (e.g. from the binary VDF reader).
+    pub fn from_nodes(nodes: Vec<VdfNode>) -> Self {
         Self {
+            nodes,
+            ..Self::new()
+        }
+    }
+
+    /// Parse binary VDF, as used by `shortcuts.vdf` and the per-app data in `appinfo.vdf`.
+    pub fn parse_binary(bytes: &[u8]) -> Result<Self> {
+        Ok(Self::from_nodes(vdf_binary::parse(bytes, None)?))
+    }
+
+    /// Parse VDF text.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut p = Parser {
             s: text.as_bytes(),
             i: 0,
-            out: BTreeMap::new(),
-            stack: Vec::new(),
-            pending_key: None,
-        }
-    }
-
-    fn parse(&mut self) -> Result<()> {
-        while self.skip_ws() {
-            match self.peek_char() {
-                Some(b'"') => {
-                    let tok = self.read_quoted()?;
-                    self.skip_ws();
-                    match self.peek_char() {
-                        Some(b'"') => {
-                            let val = self.read_quoted()?;
-                            self.add_kv(tok, val);
-                        }
-                        Some(b'{') => {
-                            self.i += 1;
-                            self.stack.push(tok);
-                        }
-                        Some(b'}') => {
-                            self.i += 1;
-                            self.pop_stack();
-                            self.pending_key = Some(tok);
-                        }
-                        _ => {
-                            self.pending_key = Some(tok);
+        };
+        let (nodes, trailing) = p.parse_children(0)?;
+        Ok(Self { nodes, trailing })
+    }
+
+    /// Top-level nodes, in file order.
+    pub fn nodes(&self) -> &[VdfNode] {
+        &self.nodes
+    }
+
+    /// Mutable top-level nodes.
+    pub fn nodes_mut(&mut self) -> &mut Vec<VdfNode> {
+        &mut self.nodes
+    }
+
+    /// Node at a `/`-separated path (first match for duplicate keys).
+    pub fn get(&self, path: &str) -> Option<&VdfNode> {
+        let (first, rest) = split_path(path);
+        let node = find(&self.nodes, first)?;
+        match rest {
+            Some(rest) => node.get(rest),
+            None => Some(node),
+        }
+    }
+
+    /// Mutable node at a `/`-separated path (first match for duplicate keys).
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut VdfNode> {
+        let (first, rest) = split_path(path);
+        let node = find_mut(&mut self.nodes, first)?;
+        match rest {
+            Some(rest) => node.get_mut(rest),
+            None => Some(node),
+        }
+    }
+
+    /// String value at a `/`-separated path.
+    pub fn get_str(&self, path: &str) -> Option<&str> {
+        self.get(path)?.as_str()
+    }
+
+    /// Top-level object `key`, appended if missing. A string value under that key is replaced.
+    pub fn object_mut(&mut self, key: &str) -> &mut VdfNode {
+        object_in(&mut self.nodes, key)
+    }
+
+    /// Serialize the document.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        write_nodes(&self.nodes, 0, &mut out);
+        out.push_str(&self.trailing);
+        out
+    }
+
+    /// Serialize the document as binary VDF (conditionals and formatting are not kept).
+    pub fn to_binary(&self) -> Result<Vec<u8>> {
+        vdf_binary::write(&self.nodes)
+    }
+
+    /// Flatten string values into `Path/To/Key` entries.
+    ///
+    /// Duplicate keys keep the last value; nodes whose conditional does not hold on this
+    /// platform are skipped.
+    pub fn flatten(&self) -> BTreeMap<String, String> {
+        let mut out = BTreeMap::new();
+        flatten(&self.nodes, "", &mut out);
+        out
+    }
+}
+
+impl fmt::Display for VdfDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_text())
+    }
+}
+
+/// A key with either a string value or ordered children.
+///
+/// Equality compares keys, values and conditionals, not source formatting or binary types.
+#[derive(Debug, Clone)]
+pub struct VdfNode {
+    key: String,
+    value: VdfValue,
+    condition: Option<String>,
+    binary_type: Option<BinaryType>,
+    format: Option<NodeFormat>,
+}
+
+/// Value of a [`VdfNode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VdfValue {
+    /// A string value: `"key" "value"`.
+    String(String),
+    /// Nested children: `"key" { ... }`.
+    Object(Vec<VdfNode>),
+}
+
+/// Source text around a parsed node, used to write it back unchanged.
+#[derive(Debug, Clone, Default)]
+struct NodeFormat {
+    /// Whitespace and comments before the key.
+    leading: String,
+    raw_key: String,
+    /// Trivia after the key (before the value, conditional or `{`).
+    sep: String,
+    /// Raw value token; `None` for objects.
+    raw_value: Option<String>,
+    /// Trivia before the conditional (strings only), and the raw `[...]` token.
+    raw_condition: Option<(String, String)>,
+    /// Trivia between an object's conditional and its `{`.
+    sep_open: String,
+    /// Trivia before an object's closing `}`; `None` for strings.
+    inner_trailing: Option<String>,
+}
+
+impl PartialEq for VdfNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.value == other.value && self.condition == other.condition
+    }
+}
+
+impl Eq for VdfNode {}
+
+impl VdfNode {
+    /// Create a string node.
+    pub fn string(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: VdfValue::String(value.into()),
+            condition: None,
+            binary_type: None,
+            format: None,
+        }
+    }
+
+    /// Create an empty object node.
+    pub fn object(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: VdfValue::Object(Vec::new()),
+            condition: None,
+            binary_type: None,
+            format: None,
+        }
+    }
+
+    /// Key.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Value.
+    pub fn value(&self) -> &VdfValue {
+        &self.value
+    }
+
+    /// Mutable value.
+    pub fn value_mut(&mut self) -> &mut VdfValue {
+        &mut self.value
+    }
+
+    /// String value, if this is a string node.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            VdfValue::String(s) => Some(s),
+            VdfValue::Object(_) => None,
+        }
+    }
+
+    /// Children (empty for string nodes).
+    pub fn children(&self) -> &[VdfNode] {
+        match &self.value {
+            VdfValue::Object(c) => c,
+            VdfValue::String(_) => &[],
+        }
+    }
+
+    /// Mutable children. A string node is turned into an empty object first.
+    pub fn children_mut(&mut self) -> &mut Vec<VdfNode> {
+        if matches!(self.value, VdfValue::String(_)) {
+            self.value = VdfValue::Object(Vec::new());
+        }
+        match &mut self.value {
+            VdfValue::Object(c) => c,
+            VdfValue::String(_) => unreachable!(),
+        }
+    }
+
+    /// First direct child named `key`.
+    pub fn child(&self, key: &str) -> Option<&VdfNode> {
+        find(self.children(), key)
+    }
+
+    /// All direct children named `key` (VDF allows duplicates).
+    pub fn children_named<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a VdfNode> + 'a {
+        self.children()
+            .iter()
+            .filter(move |c| c.key.eq_ignore_ascii_case(key))
+    }
+
+    /// Descendant at a `/`-separated path relative to this node.
+    pub fn get(&self, path: &str) -> Option<&VdfNode> {
+        let (first, rest) = split_path(path);
+        let node = self.child(first)?;
+        match rest {
+            Some(rest) => node.get(rest),
+            None => Some(node),
+        }
+    }
+
+    /// Mutable descendant at a `/`-separated path relative to this node.
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut VdfNode> {
+        let (first, rest) = split_path(path);
+        let VdfValue::Object(children) = &mut self.value else {
+            return None;
+        };
+        let node = find_mut(children, first)?;
+        match rest {
+            Some(rest) => node.get_mut(rest),
+            None => Some(node),
+        }
+    }
+
+    /// String value of the descendant at a `/`-separated path.
+    pub fn get_str(&self, path: &str) -> Option<&str> {
+        self.get(path)?.as_str()
+    }
+
+    /// Child object `key`, appended if missing. A string value under that key is replaced.
+    pub fn object_mut(&mut self, key: &str) -> &mut VdfNode {
+        object_in(self.children_mut(), key)
+    }
+
+    /// Set string child `key`, keeping its position (and formatting) if it exists.
+    pub fn set_string(&mut self, key: &str, value: impl Into<String>) {
+        let value = value.into();
+        let children = self.children_mut();
+        match find_mut(children, key) {
+            Some(c) => c.value = VdfValue::String(value),
+            None => children.push(VdfNode::string(key, value)),
+        }
+    }
+
+    /// Append a child node.
+    pub fn push(&mut self, node: VdfNode) {
+        self.children_mut().push(node);
+    }
+
+    /// Remove and return the first direct child named `key`.
+    pub fn remove(&mut self, key: &str) -> Option<VdfNode> {
+        let VdfValue::Object(children) = &mut self.value else {
+            return None;
+        };
+        let idx = children
+            .iter()
+            .position(|c| c.key.eq_ignore_ascii_case(key))?;
+        Some(children.remove(idx))
+    }
+
+    /// Binary VDF value type, for nodes read from (or destined for) binary files.
+    ///
+    /// Numeric values are stored as decimal strings; `None` means a plain string.
+    pub fn binary_type(&self) -> Option<BinaryType> {
+        self.binary_type
+    }
+
+    /// Set the binary VDF value type used when writing this node to a binary file.
+    pub fn set_binary_type(&mut self, binary_type: Option<BinaryType>) {
+        self.binary_type = binary_type;
+    }
+
+    pub(crate) fn typed(key: String, value: String, binary_type: BinaryType) -> Self {
+        Self {
+            binary_type: Some(binary_type),
+            ..Self::string(key, value)
+        }
+    }
+
+    pub(crate) fn with_children(key: String, children: Vec<VdfNode>) -> Self {
+        Self {
+            value: VdfValue::Object(children),
+            ..Self::object(key)
+        }
+    }
+
+    /// Platform conditional without brackets, e.g. `$WIN32` or `!$OSX`.
+    pub fn condition(&self) -> Option<&str> {
+        self.condition.as_deref()
+    }
+
+    /// Set or clear the platform conditional (without brackets).
+    pub fn set_condition(&mut self, condition: Option<String>) {
+        self.condition = condition;
+    }
+
+    /// True if the node has no conditional or its conditional holds on this platform.
+    pub fn applies_to_current_platform(&self) -> bool {
+        self.condition.as_deref().map_or(true, condition_holds)
+    }
+}
+
+fn split_path(path: &str) -> (&str, Option<&str>) {
+    match path.split_once('/') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (path, None),
+    }
+}
+
+fn find<'a>(nodes: &'a [VdfNode], key: &str) -> Option<&'a VdfNode> {
+    nodes.iter().find(|n| n.key.eq_ignore_ascii_case(key))
+}
+
+fn find_mut<'a>(nodes: &'a mut [VdfNode], key: &str) -> Option<&'a mut VdfNode> {
+    nodes.iter_mut().find(|n| n.key.eq_ignore_ascii_case(key))
+}
+
+fn object_in<'a>(nodes: &'a mut Vec<VdfNode>, key: &str) -> &'a mut VdfNode {
+    let idx = match nodes.iter().position(|c| c.key.eq_ignore_ascii_case(key)) {
+        Some(i) => i,
+        None => {
+            nodes.push(VdfNode::object(key));
+            nodes.len() - 1
+        }
+    };
+    let node = &mut nodes[idx];
+    node.children_mut();
+    node
+}
+
+/// Evaluate a conditional such as `$WIN32`, `!$OSX` or `$LINUX || $OSX`.
+fn condition_holds(cond: &str) -> bool {
+    cond.split("||").any(|any| {
+        any.split("&&").all(|atom| {
+            let atom = atom.trim();
+            let (negate, name) = match atom.strip_prefix('!') {
+                Some(name) => (true, name.trim()),
+                None => (false, atom),
+            };
+            let value = match name.to_ascii_uppercase().as_str() {
+                "$WIN32" | "$WIN64" | "$WINDOWS" => cfg!(target_os = "windows"),
+                "$LINUX" | "$POSIX" => cfg!(target_os = "linux"),
+                _ => false,
+            };
+            value != negate
+        })
+    })
+}
+
+fn flatten(nodes: &[VdfNode], prefix: &str, out: &mut BTreeMap<String, String>) {
+    for node in nodes.iter().filter(|n| n.applies_to_current_platform()) {
+        let path = format!("{prefix}{}", node.key);
+        match &node.value {
+            VdfValue::String(v) => {
+                out.insert(path, v.clone());
+            }
+            VdfValue::Object(children) => flatten(children, &format!("{path}/"), out),
+        }
+    }
+}
+
+fn write_nodes(nodes: &[VdfNode], depth: usize, out: &mut String) {
+    let newline_indent = format!("\n{}", "\t".repeat(depth));
+    for node in nodes {
+        let f = node.format.as_ref();
+
+        match f {
+            Some(f) => out.push_str(&f.leading),
+            None if out.is_empty() => {}
+            None => out.push_str(&newline_indent),
+        }
+
+        match f {
+            Some(f) if decode_token(&f.raw_key) == node.key => out.push_str(&f.raw_key),
+            _ => out.push_str(&quote(&node.key)),
+        }
+
+        match &node.value {
+            VdfValue::String(v) => {
+                let f = f.filter(|f| f.raw_value.is_some());
+                out.push_str(f.map_or("\t\t", |f| f.sep.as_str()));
+                match f.and_then(|f| f.raw_value.as_ref()) {
+                    Some(raw) if decode_token(raw) == *v => out.push_str(raw),
+                    _ => out.push_str(&quote(v)),
+                }
+                if let Some(cond) = &node.condition {
+                    match f.and_then(|f| f.raw_condition.as_ref()) {
+                        Some((gap, raw)) if raw_condition_is(raw, cond) => {
+                            out.push_str(gap);
+                            out.push_str(raw);
                         }
+                        _ => out.push_str(&format!(" [{cond}]")),
                     }
                 }
-                Some(b'{') => {
-                    self.i += 1;
-                    if let Some(k) = self.pending_key.take() {
-                        self.stack.push(k);
+            }
+            VdfValue::Object(children) => {
+                let f = f.filter(|f| f.inner_trailing.is_some());
+                match (&node.condition, f) {
+                    (Some(cond), Some(f))
+                        if f.raw_condition
+                            .as_ref()
+                            .is_some_and(|(_, raw)| raw_condition_is(raw, cond)) =>
+                    {
+                        out.push_str(&f.sep);
+                        out.push_str(&f.raw_condition.as_ref().unwrap().1);
+                        out.push_str(&f.sep_open);
                     }
+                    (Some(cond), _) => {
+                        out.push_str(&format!(" [{cond}]"));
+                        out.push_str(&newline_indent);
+                    }
+                    (None, Some(f)) if f.raw_condition.is_none() => out.push_str(&f.sep),
+                    (None, _) => out.push_str(&newline_indent),
                 }
-                Some(b'}') => {
-                    self.i += 1;
-                    self.pop_stack();
-                }
-                _ => {
-                    self.i += 1;
+                out.push('{');
+                write_nodes(children, depth + 1, out);
+                match f.and_then(|f| f.inner_trailing.as_ref()) {
+                    Some(t) => out.push_str(t),
+                    None => out.push_str(&newline_indent),
                 }
+                out.push('}');
             }
         }
+    }
+}
 
-        if !self.stack.is_empty() {
-            return Err(Arma3Error::SteamConfig {
-                message: "unclosed braces in VDF".into(),
-            });
+fn raw_condition_is(raw: &str, cond: &str) -> bool {
+    raw.get(1..raw.len() - 1) == Some(cond)
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
         }
-        Ok(())
     }
+    out.push('"');
+    out
+}
 
-    fn add_kv(&mut self, k: String, v: String) {
-        let mut path = String::new();
-        for seg in &self.stack {
-            path.push_str(seg);
-            path.push('/');
+/// Decode a raw quoted or bare token.
+fn decode_token(raw: &str) -> String {
+    let Some(inner) = raw.strip_prefix('"') else {
+        return raw.to_string();
+    };
+    let inner = inner.strip_suffix('"').unwrap_or(inner);
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => {}
         }
-        path.push_str(&k);
-        self.out.insert(path, v);
     }
+    out
+}
 
-    fn pop_stack(&mut self) {
-        let _ = self.stack.pop();
-    }
+struct Parser<'a> {
+    s: &'a [u8],
+    i: usize,
+}
 
-    fn skip_ws(&mut self) -> bool {
-        while self.i < self.s.len() && self.s[self.i].is_ascii_whitespace() {
-            self.i += 1;
+impl<'a> Parser<'a> {
+    /// Parse nodes until the matching `}` (consumed) or, at depth 0, the end of input.
+    ///
+    /// Returns the nodes and the trivia before the terminator.
+    fn parse_children(&mut self, depth: usize) -> Result<(Vec<VdfNode>, String)> {
+        let mut out = Vec::new();
+        // Text not yet attached to a node. Malformed fragments (stray `}` at top level, keys
+        // without a value) are kept here so they survive a round trip.
+        let mut trivia = String::new();
+
+        loop {
+            trivia.push_str(&self.read_trivia()?);
+            match self.peek() {
+                None => {
+                    if depth > 0 {
+                        return Err(Arma3Error::SteamConfig {
+                            message: "unclosed braces in VDF".into(),
+                        });
+                    }
+                    return Ok((out, trivia));
+                }
+                Some(b'}') => {
+                    self.i += 1;
+                    if depth > 0 {
+                        return Ok((out, trivia));
+                    }
+                    trivia.push('}');
+                }
+                Some(b'{') => return Err(self.error("unexpected '{' without a key")),
+                Some(b'[') => trivia.push_str(&self.read_condition()?),
+                Some(_) => {
+                    let key_start = self.i;
+                    let raw_key = self.read_token()?;
+                    let mut format = NodeFormat {
+                        leading: std::mem::take(&mut trivia),
+                        raw_key: raw_key.clone(),
+                        sep: self.read_trivia()?,
+                        ..NodeFormat::default()
+                    };
+                    let mut condition = None;
+
+                    if self.peek() == Some(b'[') {
+                        let raw = self.read_condition()?;
+                        condition = Some(raw[1..raw.len() - 1].to_string());
+                        format.raw_condition = Some((String::new(), raw));
+                        format.sep_open = self.read_trivia()?;
+                        if self.peek() != Some(b'{') {
+                            return Err(self.error("expected '{' after conditional"));
+                        }
+                    }
+
+                    match self.peek() {
+                        Some(b'{') => {
+                            self.i += 1;
+                            let (children, inner) = self.parse_children(depth + 1)?;
+                            format.inner_trailing = Some(inner);
+                            out.push(VdfNode {
+                                key: decode_token(&raw_key),
+                                value: VdfValue::Object(children),
+                                condition,
+                                binary_type: None,
+                                format: Some(format),
+                            });
+                        }
+                        None | Some(b'}') => {
+                            // Key without a value: keep the text, drop the entry.
+                            trivia = format.leading;
+                            trivia.push_str(&self.text(key_start)?);
+                        }
+                        Some(_) => {
+                            let raw_value = self.read_token()?;
+
+                            // A conditional may follow the value on the same line.
+                            let save = self.i;
+                            let mut gap = String::new();
+                            while let Some(c @ (b' ' | b'\t')) = self.peek() {
+                                gap.push(c as char);
+                                self.i += 1;
+                            }
+                            if self.peek() == Some(b'[') {
+                                let raw = self.read_condition()?;
+                                condition = Some(raw[1..raw.len() - 1].to_string());
+                                format.raw_condition = Some((gap, raw));
+                            } else {
+                                self.i = save;
+                            }
+
+                            out.push(VdfNode {
+                                key: decode_token(&raw_key),
+                                value: VdfValue::String(decode_token(&raw_value)),
+                                condition,
+                                binary_type: None,
+                                format: Some(NodeFormat {
+                                    raw_value: Some(raw_value),
+                                    ..format
+                                }),
+                            });
+                        }
+                    }
+                }
+            }
         }
-        self.i < self.s.len()
     }
 
-    fn peek_char(&self) -> Option<u8> {
+    fn peek(&self) -> Option<u8> {
         self.s.get(self.i).copied()
     }
 
-    fn read_quoted(&mut self) -> Result<String> {
-        if self.peek_char() != Some(b'"') {
-            return Err(Arma3Error::SteamConfig {
-                message: "expected quoted string".into(),
-            });
+    fn error(&self, what: &str) -> Arma3Error {
+        Arma3Error::SteamConfig {
+            message: format!("{what} at byte {} in VDF", self.i),
         }
-        self.i += 1;
-        let mut out = Vec::new();
-        let mut escape = false;
+    }
 
-        while self.i < self.s.len() {
-            let c = self.s[self.i];
-            self.i += 1;
+    fn text(&self, start: usize) -> Result<String> {
+        Ok(String::from_utf8(self.s[start..self.i].to_vec())?)
+    }
 
-            if escape {
-                out.push(c);
-                escape = false;
-                continue;
+    /// Whitespace and `//` comments.
+    fn read_trivia(&mut self) -> Result<String> {
+        let start = self.i;
+        loop {
+            match self.peek() {
+                Some(c) if c.is_ascii_whitespace() => self.i += 1,
+                Some(b'/') if self.s.get(self.i + 1) == Some(&b'/') => {
+                    while self.i < self.s.len() && self.s[self.i] != b'\n' {
+                        self.i += 1;
+                    }
+                }
+                _ => break,
             }
+        }
+        self.text(start)
+    }
 
-            if c == b'\\' {
-                escape = true;
-                continue;
+    /// A raw quoted (including quotes) or bare token.
+    fn read_token(&mut self) -> Result<String> {
+        let start = self.i;
+        if self.peek() == Some(b'"') {
+            self.i += 1;
+            while self.i < self.s.len() {
+                let c = self.s[self.i];
+                self.i += 1;
+                if c == b'\\' {
+                    self.i = (self.i + 1).min(self.s.len());
+                } else if c == b'"' {
+                    break;
+                }
             }
-
-            if c == b'"' {
-                break;
+        } else {
+            while let Some(c) = self.peek() {
+                if c.is_ascii_whitespace() || c == b'{' || c == b'}' || c == b'"' {
+                    break;
+                }
+                self.i += 1;
             }
-
-            out.push(c);
         }
+        self.text(start)
+    }
 
-        Ok(String::from_utf8(out)?)
+    /// A raw `[...]` conditional.
+    fn read_condition(&mut self) -> Result<String> {
+        let start = self.i;
+        while self.i < self.s.len() && self.s[self.i] != b']' {
+            self.i += 1;
+        }
+        if self.i >= self.s.len() {
+            return Err(self.error("unterminated conditional"));
+        }
+        self.i += 1;
+        self.text(start)
     }
 }
 
@@ -188,4 +750,47 @@ mod tests {
             .unwrap();
         assert_eq!(val, "GE-Proton");
     }
+
+    #[test]
+    fn round_trips_untouched_files_byte_for_byte() {
+        let txt = "// generated\r\n\"root\"\r\n{\r\n\t\"path\"\t\t\"D:\\\\Steam\" // library\r\n\tbare_key bare_value\r\n\t\"dup\" \"1\"\r\n\t\"dup\" \"2\"\r\n\t\"win\" \"yes\" [$WIN32]\r\n\t\"obj\" [!$WIN32]\r\n\t{\r\n\t}\r\n\t\"esc\" \"a\\tb\\n\"\r\n}\r\n";
+        let doc = VdfDocument::parse(txt).unwrap();
+        assert_eq!(doc.to_text(), txt);
+
+        let root = doc.get("root").unwrap();
+        assert_eq!(root.get_str("path"), Some("D:\\Steam"));
+        assert_eq!(root.get_str("bare_key"), Some("bare_value"));
+        let dups: Vec<_> = root
+            .children_named("dup")
+            .filter_map(|n| n.as_str())
+            .collect();
+        assert_eq!(dups, ["1", "2"]);
+        assert_eq!(root.child("win").unwrap().condition(), Some("$WIN32"));
+        assert_eq!(root.get_str("esc"), Some("a\tb\n"));
+    }
+
+    #[test]
+    fn edits_keep_surrounding_text() {
+        let txt = "\"root\"\n{\n\t\"a\"\t\t\"1\" // keep me\n\t\"b\"\t\t\"2\"\n}\n";
+        let mut doc = VdfDocument::parse(txt).unwrap();
+        let root = doc.object_mut("root");
+        root.set_string("b", "3");
+        root.object_mut("c").set_string("d", "4");
+
+        assert_eq!(
+            doc.to_text(),
+            "\"root\"\n{\n\t\"a\"\t\t\"1\" // keep me\n\t\"b\"\t\t\"3\"\n\t\"c\"\n\t{\n\t\t\"d\"\t\t\"4\"\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn writes_new_documents_in_steam_format() {
+        let mut doc = VdfDocument::new();
+        doc.object_mut("AppState")
+            .set_string("installdir", "Arma 3");
+        assert_eq!(
+            doc.to_text(),
+            "\"AppState\"\n{\n\t\"installdir\"\t\t\"Arma 3\"\n}\n"
+        );
+    }
 }
@@ -0,0 +1,290 @@
+use crate::error::{Arma3Error, Result};
+use crate::steam::vdf::VdfNode;
+
+const TYPE_OBJECT: u8 = 0x00;
+const TYPE_STRING: u8 = 0x01;
+const TYPE_INT32: u8 = 0x02;
+const TYPE_FLOAT32: u8 = 0x03;
+const TYPE_POINTER: u8 = 0x04;
+const TYPE_WIDE_STRING: u8 = 0x05;
+const TYPE_COLOR: u8 = 0x06;
+const TYPE_UINT64: u8 = 0x07;
+const TYPE_END: u8 = 0x08;
+const TYPE_INT64: u8 = 0x0A;
+const TYPE_END_ALT: u8 = 0x0B;
+
+/// Value type of a binary VDF entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryType {
+    /// NUL-terminated UTF-8 string.
+    String,
+    /// Signed 32-bit integer.
+    Int32,
+    /// 32-bit float.
+    Float32,
+    /// 32-bit pointer value.
+    Pointer,
+    /// NUL-terminated UTF-16LE string.
+    WideString,
+    /// 32-bit RGBA color.
+    Color,
+    /// Unsigned 64-bit integer.
+    UInt64,
+    /// Signed 64-bit integer.
+    Int64,
+}
+
+/// Parse binary VDF nodes until the closing end marker (or end of input).
+///
+/// With a string table (appinfo v29+), keys are stored as `u32` indices into it.
+pub(crate) fn parse(bytes: &[u8], strings: Option<&[String]>) -> Result<Vec<VdfNode>> {
+    let mut r = Reader {
+        s: bytes,
+        i: 0,
+        strings,
+    };
+    r.read_nodes(0)
+}
+
+/// Serialize nodes as binary VDF, followed by the closing end marker.
+///
+/// String nodes are written with their [`BinaryType`] (plain strings if unset); numeric types
+/// must hold a decimal value of the right range.
+pub(crate) fn write(nodes: &[VdfNode]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    write_nodes(nodes, &mut out)?;
+    Ok(out)
+}
+
+fn write_nodes(nodes: &[VdfNode], out: &mut Vec<u8>) -> Result<()> {
+    for node in nodes {
+        let Some(value) = node.as_str() else {
+            out.push(TYPE_OBJECT);
+            write_cstring(node.key(), out)?;
+            write_nodes(node.children(), out)?;
+            continue;
+        };
+
+        let binary_type = node.binary_type().unwrap_or(BinaryType::String);
+        out.push(match binary_type {
+            BinaryType::String => TYPE_STRING,
+            BinaryType::Int32 => TYPE_INT32,
+            BinaryType::Float32 => TYPE_FLOAT32,
+            BinaryType::Pointer => TYPE_POINTER,
+            BinaryType::WideString => TYPE_WIDE_STRING,
+            BinaryType::Color => TYPE_COLOR,
+            BinaryType::UInt64 => TYPE_UINT64,
+            BinaryType::Int64 => TYPE_INT64,
+        });
+        write_cstring(node.key(), out)?;
+
+        let bad_value = || Arma3Error::SteamConfig {
+            message: format!(
+                "value '{value}' of '{}' is not a valid {binary_type:?}",
+                node.key()
+            ),
+        };
+        match binary_type {
+            BinaryType::String => write_cstring(value, out)?,
+            BinaryType::Int32 => {
+                let v: i32 = value.parse().map_err(|_| bad_value())?;
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            BinaryType::Float32 => {
+                let v: f32 = value.parse().map_err(|_| bad_value())?;
+                out.extend_from_slice(&v.to_bits().to_le_bytes());
+            }
+            BinaryType::Pointer | BinaryType::Color => {
+                let v: u32 = value.parse().map_err(|_| bad_value())?;
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            BinaryType::WideString => {
+                for unit in value.encode_utf16().chain([0]) {
+                    out.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+            BinaryType::UInt64 => {
+                let v: u64 = value.parse().map_err(|_| bad_value())?;
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            BinaryType::Int64 => {
+                let v: i64 = value.parse().map_err(|_| bad_value())?;
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+    }
+    out.push(TYPE_END);
+    Ok(())
+}
+
+fn write_cstring(s: &str, out: &mut Vec<u8>) -> Result<()> {
+    if s.contains('\0') {
+        return Err(Arma3Error::SteamConfig {
+            message: format!("binary VDF strings cannot contain NUL: {s:?}"),
+        });
+    }
+    out.extend_from_slice(s.as_bytes());
+    out.push(0);
+    Ok(())
+}
+
+pub(crate) struct Reader<'a> {
+    pub(crate) s: &'a [u8],
+    pub(crate) i: usize,
+    strings: Option<&'a [String]>,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(s: &'a [u8]) -> Self {
+        Self {
+            s,
+            i: 0,
+            strings: None,
+        }
+    }
+
+    fn read_nodes(&mut self, depth: usize) -> Result<Vec<VdfNode>> {
+        let mut out = Vec::new();
+        loop {
+            let Some(t) = self.s.get(self.i).copied() else {
+                if depth == 0 {
+                    return Ok(out);
+                }
+                return Err(self.error("unexpected end of data"));
+            };
+            self.i += 1;
+
+            if t == TYPE_END || t == TYPE_END_ALT {
+                return Ok(out);
+            }
+
+            let key = self.read_key()?;
+            let node = match t {
+                TYPE_OBJECT => VdfNode::with_children(key, self.read_nodes(depth + 1)?),
+                TYPE_STRING => VdfNode::typed(key, self.cstring()?, BinaryType::String),
+                TYPE_INT32 => VdfNode::typed(key, self.i32()?.to_string(), BinaryType::Int32),
+                TYPE_FLOAT32 => VdfNode::typed(
+                    key,
+                    f32::from_bits(self.u32()?).to_string(),
+                    BinaryType::Float32,
+                ),
+                TYPE_POINTER => VdfNode::typed(key, self.u32()?.to_string(), BinaryType::Pointer),
+                TYPE_WIDE_STRING => {
+                    VdfNode::typed(key, self.wide_cstring()?, BinaryType::WideString)
+                }
+                TYPE_COLOR => VdfNode::typed(key, self.u32()?.to_string(), BinaryType::Color),
+                TYPE_UINT64 => VdfNode::typed(key, self.u64()?.to_string(), BinaryType::UInt64),
+                TYPE_INT64 => {
+                    VdfNode::typed(key, (self.u64()? as i64).to_string(), BinaryType::Int64)
+                }
+                other => {
+                    self.i -= 1;
+                    return Err(self.error(&format!("unknown value type 0x{other:02x}")));
+                }
+            };
+            out.push(node);
+        }
+    }
+
+    fn read_key(&mut self) -> Result<String> {
+        match self.strings {
+            Some(strings) => {
+                let idx = self.u32()? as usize;
+                strings
+                    .get(idx)
+                    .cloned()
+                    .ok_or_else(|| self.error(&format!("string table index {idx} out of range")))
+            }
+            None => self.cstring(),
+        }
+    }
+
+    pub(crate) fn error(&self, what: &str) -> Arma3Error {
+        Arma3Error::SteamConfig {
+            message: format!("{what} at byte {} in binary VDF", self.i),
+        }
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let end = self
+            .i
+            .checked_add(n)
+            .filter(|end| *end <= self.s.len())
+            .ok_or_else(|| self.error("unexpected end of data"))?;
+        let out = &self.s[self.i..end];
+        self.i = end;
+        Ok(out)
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        Ok(self.u32()? as i32)
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64> {
+        let b = self.take(8)?;
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(b);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    pub(crate) fn cstring(&mut self) -> Result<String> {
+        let rest = &self.s[self.i..];
+        let len = rest
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| self.error("unterminated string"))?;
+        let s = String::from_utf8(rest[..len].to_vec())?;
+        self.i += len + 1;
+        Ok(s)
+    }
+
+    fn wide_cstring(&mut self) -> Result<String> {
+        let mut units = Vec::new();
+        loop {
+            let b = self.take(2)?;
+            let u = u16::from_le_bytes([b[0], b[1]]);
+            if u == 0 {
+                break;
+            }
+            units.push(u);
+        }
+        String::from_utf16(&units).map_err(|_| self.error("invalid UTF-16 string"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::steam::vdf::VdfDocument;
+
+    #[test]
+    fn parses_shortcuts_layout() {
+        let mut b = Vec::new();
+        b.extend_from_slice(b"\x00shortcuts\x00");
+        b.extend_from_slice(b"\x000\x00");
+        b.extend_from_slice(b"\x02appid\x00");
+        b.extend_from_slice(&(-1_234_567_i32).to_le_bytes());
+        b.extend_from_slice(b"\x01AppName\x00Arma 3 \xe2\x80\x93 Unit\x00");
+        b.extend_from_slice(b"\x00tags\x00\x010\x00favorite\x00\x08");
+        b.extend_from_slice(b"\x08\x08\x08");
+
+        let doc = VdfDocument::parse_binary(&b).unwrap();
+        let entry = doc.get("shortcuts/0").unwrap();
+        assert_eq!(entry.get_str("appid"), Some("-1234567"));
+        assert_eq!(
+            entry.child("appid").unwrap().binary_type(),
+            Some(BinaryType::Int32)
+        );
+        assert_eq!(entry.get_str("AppName"), Some("Arma 3 \u{2013} Unit"));
+        assert_eq!(entry.get_str("tags/0"), Some("favorite"));
+
+        let text = VdfDocument::parse(&doc.to_text()).unwrap();
+        assert_eq!(text, doc);
+        assert_eq!(doc.to_binary().unwrap(), b);
+    }
+}
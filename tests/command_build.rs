@@ -1,5 +1,8 @@
 //! Integration tests for planning.
-use arma3_launcher::{Arma3Install, LaunchMode, Launcher, LocalMod, ModSet};
+use arma3_launcher::{
+    Arma3Error, Arma3Install, DetectionContext, LaunchMode, Launcher, LocalMod, ModSet,
+    ProtonOptions,
+};
 use std::fs;
 use tempfile::tempdir;
 
@@ -60,3 +63,494 @@ fn adds_mod_arg_when_missing() {
         .find(|a| a.starts_with("-mod="));
     assert!(mod_arg.is_some(), "expected -mod= argument to be added");
 }
+
+#[test]
+fn rejects_proton_options_through_steam() {
+    let d = tempdir().unwrap();
+    let game = d.path().join("Arma 3");
+    fs::create_dir_all(&game).unwrap();
+    let exe = if cfg!(target_os = "windows") {
+        "arma3_x64.exe"
+    } else {
+        "arma3.x86_64"
+    };
+    fs::write(game.join(exe), b"").unwrap();
+
+    let install = Arma3Install::new(&game).unwrap();
+    let launcher = Launcher::new(install)
+        .launch_mode(LaunchMode::ThroughSteam)
+        .proton_options(ProtonOptions::new().no_fsync(true));
+
+    let err = launcher.plan().unwrap_err();
+    assert!(matches!(err, Arma3Error::UnsupportedOption { .. }));
+    assert!(err.to_string().contains("PROTON_NO_FSYNC"));
+
+    let err = launcher
+        .proton_options(ProtonOptions::new())
+        .disable_esync(true)
+        .plan()
+        .unwrap_err();
+    assert!(matches!(err, Arma3Error::UnsupportedOption { .. }));
+    assert!(err.to_string().contains("PROTON_NO_ESYNC"));
+}
+
+#[test]
+fn splices_mods_into_steam_command() {
+    let d = tempdir().unwrap();
+    let game = d.path().join("Arma 3");
+    fs::create_dir_all(&game).unwrap();
+    let exe = if cfg!(target_os = "windows") {
+        "arma3_x64.exe"
+    } else {
+        "arma3.x86_64"
+    };
+    fs::write(game.join(exe), b"").unwrap();
+
+    let mod_dir = d.path().join("@mymod");
+    fs::create_dir_all(mod_dir.join("addons")).unwrap();
+    fs::write(mod_dir.join("addons").join("stub.pbo"), "data").unwrap();
+
+    let install = Arma3Install::new(&game).unwrap();
+    let launcher = Launcher::new(install)
+        .mod_enabled(LocalMod::new(&mod_dir).unwrap())
+        .arg("-noSplash");
+
+    let command = [
+        "/steam/SteamLinuxRuntime_sniper/_v2-entry-point",
+        "--verb=waitforexitandrun",
+        "--",
+        "/steam/common/Proton 9.0/proton",
+        "waitforexitandrun",
+        "/steam/common/Arma 3/arma3launcher.exe",
+        "-world=empty",
+    ];
+    let plan = launcher.wrap_steam_command(command).unwrap();
+    assert_eq!(plan.program().to_string_lossy(), command[0]);
+
+    let args: Vec<String> = plan
+        .args()
+        .iter()
+        .map(|a| a.to_string_lossy().to_string())
+        .collect();
+    assert_eq!(args[..5], command[1..6]);
+    assert_eq!(args[5], "-nolauncher");
+    assert_eq!(args[6], "-noSplash");
+    assert!(args[7].starts_with("-mod="));
+    assert_eq!(args[8], "-world=empty");
+
+    // On Windows `%command%` is just the executable.
+    let args = |command: &[&str]| -> Vec<String> {
+        let plan = launcher.wrap_steam_command(command).unwrap();
+        assert_eq!(plan.program().to_string_lossy(), command[0]);
+        plan.args()
+            .iter()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect()
+    };
+    let args_game = args(&[
+        r"C:\Steam\steamapps\common\Arma 3\arma3_x64.exe",
+        "-world=empty",
+    ]);
+    assert_eq!(args_game[0], "-noSplash");
+    assert!(args_game[1].starts_with("-mod="));
+    assert_eq!(args_game[2], "-world=empty");
+    let args_launcher = args(&[r"C:\Steam\steamapps\common\Arma 3\arma3launcher.exe"]);
+    assert_eq!(args_launcher[..2], ["-nolauncher", "-noSplash"]);
+    assert!(args_launcher[2].starts_with("-mod="));
+
+    let found = Arma3Install::from_steam_command([game.join(exe)]).unwrap();
+    assert_eq!(found.game_dir(), game);
+
+    let err = launcher
+        .clone()
+        .proton_options(ProtonOptions::new().no_fsync(true))
+        .wrap_steam_command(["/usr/bin/arma3launcher"])
+        .unwrap_err();
+    assert!(matches!(err, Arma3Error::UnsupportedOption { .. }));
+}
+
+#[test]
+fn builds_steam_url_plan() {
+    let d = tempdir().unwrap();
+    let game = d.path().join("Arma 3");
+    fs::create_dir_all(&game).unwrap();
+    let exe = if cfg!(target_os = "windows") {
+        "arma3_x64.exe"
+    } else {
+        "arma3.x86_64"
+    };
+    fs::write(game.join(exe), b"").unwrap();
+
+    let install = Arma3Install::new(&game).unwrap();
+    let plan = Launcher::new(install)
+        .launch_mode(LaunchMode::SteamUrl)
+        .url_opener("/usr/bin/gio-open")
+        .arg("-noSplash")
+        .plan()
+        .unwrap();
+
+    assert_eq!(plan.program().to_string_lossy(), "/usr/bin/gio-open");
+    assert_eq!(
+        plan.args(),
+        ["steam://run/107410//-nolauncher%20-noSplash/"]
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn direct_session_reports_exit_status() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let d = tempdir().unwrap();
+    let game = d.path().join("Arma 3");
+    fs::create_dir_all(&game).unwrap();
+    let exe = game.join("arma3.x86_64");
+    fs::write(&exe, "#!/bin/sh\nexit 3\n").unwrap();
+    fs::set_permissions(&exe, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let install = Arma3Install::new(&game).unwrap();
+    let mut session = Launcher::new(install)
+        .launch_mode(LaunchMode::Direct)
+        .start()
+        .unwrap();
+    assert!(session.pid() > 0);
+    let status = session.wait().unwrap().unwrap();
+    assert_eq!(status.code(), Some(3));
+    assert!(!session.is_running());
+    let crash = session.crash_report().unwrap().unwrap();
+    assert_eq!(crash.exit_status().and_then(|s| s.code()), Some(3));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn terminates_direct_session_tree() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Duration;
+
+    let d = tempdir().unwrap();
+    let game = d.path().join("Arma 3");
+    fs::create_dir_all(&game).unwrap();
+    let exe = game.join("arma3.x86_64");
+    // A child that ignores SIGTERM forces the escalation path.
+    fs::write(
+        &exe,
+        "#!/bin/sh\nsh -c 'trap \"\" TERM; sleep 30' &\nsleep 30\n",
+    )
+    .unwrap();
+    fs::set_permissions(&exe, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let install = Arma3Install::new(&game).unwrap();
+    let mut session = Launcher::new(install)
+        .launch_mode(LaunchMode::Direct)
+        .start()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+    session
+        .terminate_with_timeout(Duration::from_millis(500))
+        .unwrap();
+    assert!(!session.is_running());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn refuses_or_attaches_to_running_instance() {
+    use arma3_launcher::InstanceCheck;
+    use std::os::unix::fs::PermissionsExt;
+
+    let d = tempdir().unwrap();
+    let game = d.path().join("Arma 3");
+    fs::create_dir_all(&game).unwrap();
+    // A copy of `sleep` keeps the game's name, so the attach `/proc` scan can find it.
+    let exe = game.join("arma3.x86_64");
+    fs::copy("/bin/sleep", &exe).unwrap();
+    fs::set_permissions(&exe, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let ctx = DetectionContext::new().env("XDG_CACHE_HOME", d.path().join("cache"));
+    let install = Arma3Install::with_context(&game, ctx).unwrap();
+    let launcher = Launcher::new(install)
+        .launch_mode(LaunchMode::Direct)
+        .arg("30")
+        .instance_check(InstanceCheck::Error);
+    let mut first = launcher.start().unwrap();
+    assert!(d.path().join("cache/arma3-launcher").is_dir());
+
+    let err = launcher.start().unwrap_err();
+    assert!(matches!(err, Arma3Error::AlreadyRunning { pid } if pid == first.pid()));
+
+    let attached = launcher
+        .clone()
+        .instance_check(InstanceCheck::Attach)
+        .start()
+        .unwrap();
+    assert_eq!(attached.pid(), first.pid());
+
+    first.terminate().unwrap();
+    let mut again = launcher.start().unwrap();
+    again.terminate().unwrap();
+}
+
+#[test]
+fn writes_support_bundle_with_redacted_plan() {
+    use std::io::Read;
+
+    let d = tempdir().unwrap();
+    let game = d.path().join("Arma 3");
+    fs::create_dir_all(&game).unwrap();
+    #[cfg(target_os = "linux")]
+    fs::write(game.join("arma3.x86_64"), "").unwrap();
+    #[cfg(target_os = "windows")]
+    fs::write(game.join("arma3_x64.exe"), "").unwrap();
+    let cba = d.path().join("@CBA_A3");
+    fs::create_dir_all(cba.join("addons")).unwrap();
+    fs::write(cba.join("addons/cba_main.pbo"), [0u8; 64]).unwrap();
+    fs::write(
+        cba.join("meta.cpp"),
+        "protocol = 1;\npublishedid = 450814997;\nname = \"CBA_A3\";\n",
+    )
+    .unwrap();
+
+    let install = Arma3Install::new(&game).unwrap();
+    let launcher = Launcher::new(install)
+        .mod_enabled(LocalMod::new(&cba).unwrap())
+        .arg("-password=hunter2");
+    let bundle = d.path().join("bundle.zip");
+    launcher
+        .clone()
+        .launch_mode(LaunchMode::Direct)
+        .write_support_bundle(&bundle)
+        .unwrap();
+
+    let read = |name: &str| {
+        let mut zip = zip::ZipArchive::new(fs::File::open(&bundle).unwrap()).unwrap();
+        let mut s = String::new();
+        zip.by_name(name).unwrap().read_to_string(&mut s).unwrap();
+        s
+    };
+    let plan = read("launch_plan.txt");
+    assert!(plan.contains("-password=<redacted>"), "{plan}");
+    assert!(!plan.contains("hunter2"));
+    assert!(read("detection.txt").contains("game dir:"));
+    let mods = read("mods.txt");
+    assert!(mods.contains("in 2 files"), "{mods}");
+    assert!(mods.contains("publishedid: 450814997"), "{mods}");
+
+    // The Steam URL percent-encodes the arguments.
+    launcher
+        .launch_mode(LaunchMode::SteamUrl)
+        .write_support_bundle(&bundle)
+        .unwrap();
+    let plan = read("launch_plan.txt");
+    assert!(plan.contains("steam://run/107410//"), "{plan}");
+    assert!(!plan.contains("hunter2"), "{plan}");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn detects_and_plans_from_fake_steam_tree() {
+    let d = tempdir().unwrap();
+    let home = d.path().join("home");
+    let steam = home.join(".local/share/Steam");
+    let library = d.path().join("library");
+    let game = library.join("steamapps/common/Arma 3");
+    fs::create_dir_all(&game).unwrap();
+    fs::write(game.join("arma3_x64.exe"), b"").unwrap();
+    fs::create_dir_all(steam.join("steamapps")).unwrap();
+    fs::write(
+        steam.join("steamapps/libraryfolders.vdf"),
+        format!(
+            r#""libraryfolders" {{ "0" {{ "path" "{}" }} "1" {{ "path" "{}" }} }}"#,
+            steam.display(),
+            library.display()
+        ),
+    )
+    .unwrap();
+    let proton = steam.join("steamapps/common/Proton 9.0");
+    fs::create_dir_all(&proton).unwrap();
+    fs::write(
+        proton.join("toolmanifest.vdf"),
+        r#""manifest" { "commandline" "/proton %verb%" }"#,
+    )
+    .unwrap();
+    let sysroot = d.path().join("sysroot");
+    fs::create_dir_all(sysroot.join("usr/lib")).unwrap();
+    fs::write(sysroot.join("usr/lib/libpng12.so.0"), b"").unwrap();
+
+    let ctx = DetectionContext::isolated().home(&home).root(&sysroot);
+    let install = ctx.detect_best_install().unwrap();
+    assert_eq!(install.game_dir(), game);
+    assert!(install.is_proton());
+    assert!(ctx
+        .trace_install_detection()
+        .probes()
+        .iter()
+        .any(|p| p.accepted() && p.path() == steam));
+
+    let plan = Launcher::new(install)
+        .launch_mode(LaunchMode::Direct)
+        .plan()
+        .unwrap();
+    assert_eq!(plan.program(), proton.join("proton"));
+    assert!(plan
+        .env()
+        .iter()
+        .any(|(k, v)| k == "STEAM_COMPAT_CLIENT_INSTALL_PATH" && *v == steam.as_os_str()));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn launches_through_the_steam_that_owns_the_install() {
+    let d = tempdir().unwrap();
+    let home = d.path();
+    let native = home.join(".local/share/Steam");
+    let flatpak = home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam");
+    for (root, persona) in [(&native, "native"), (&flatpak, "flatpak")] {
+        let game = root.join("steamapps/common/Arma 3");
+        fs::create_dir_all(&game).unwrap();
+        fs::write(game.join("arma3.x86_64"), b"").unwrap();
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::write(
+            root.join("config/loginusers.vdf"),
+            format!(
+                "\"users\" {{ \"76561197960265729\" {{ \"PersonaName\" \"{persona}\" \"MostRecent\" \"1\" }} }}"
+            ),
+        )
+        .unwrap();
+    }
+    fs::write(native.join("steam.sh"), b"").unwrap();
+
+    let ctx = DetectionContext::isolated().home(home);
+    let installs = ctx.detect_install_candidates();
+    assert_eq!(installs.len(), 2);
+    assert_eq!(ctx.detect_steam_installations().len(), 2);
+
+    let plans: Vec<_> = installs
+        .into_iter()
+        .map(|install| {
+            let plan = Launcher::new(install)
+                .launch_mode(LaunchMode::ThroughSteam)
+                .name_from_steam(true)
+                .plan()
+                .unwrap();
+            let name = plan
+                .args()
+                .iter()
+                .find_map(|a| a.to_str()?.strip_prefix("-name=").map(str::to_string));
+            (plan.program().to_path_buf(), name.unwrap())
+        })
+        .collect();
+    assert_eq!(
+        plans,
+        [
+            (native.join("steam.sh"), "native".to_string()),
+            (std::path::PathBuf::from("flatpak"), "flatpak".to_string())
+        ]
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn launches_snap_steam_and_warns_about_hidden_mods() {
+    let d = tempdir().unwrap();
+    let home = d.path();
+    let snap = home.join("snap/steam/common/.local/share/Steam");
+    let game = snap.join("steamapps/common/Arma 3");
+    fs::create_dir_all(&game).unwrap();
+    fs::write(game.join("arma3.x86_64"), b"").unwrap();
+    let hidden = home.join(".arma3/@hidden");
+    let visible = home.join("arma3/@visible");
+    for m in [&hidden, &visible] {
+        fs::create_dir_all(m.join("addons")).unwrap();
+        fs::write(m.join("addons/stub.pbo"), b"data").unwrap();
+    }
+
+    let ctx = DetectionContext::isolated().home(home);
+    let install = ctx.detect_best_install().unwrap();
+    let plan = Launcher::new(install)
+        .launch_mode(LaunchMode::ThroughSteam)
+        .mods_enabled([
+            LocalMod::new(&hidden).unwrap(),
+            LocalMod::new(&visible).unwrap(),
+        ])
+        .plan()
+        .unwrap();
+
+    assert_eq!(plan.program(), std::path::Path::new("snap"));
+    assert_eq!(plan.args()[..3], ["run", "steam", "-applaunch"]);
+    assert_eq!(plan.warnings().len(), 1, "{:?}", plan.warnings());
+    assert!(plan.warnings()[0].contains("@hidden"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn runs_proton_inside_flatpak_steam_sandbox() {
+    let d = tempdir().unwrap();
+    let home = d.path().join("home");
+    let steam = home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam");
+    let game = steam.join("steamapps/common/Arma 3");
+    fs::create_dir_all(&game).unwrap();
+    fs::write(game.join("arma3_x64.exe"), b"").unwrap();
+    let proton = steam.join("steamapps/common/Proton 9.0");
+    fs::create_dir_all(&proton).unwrap();
+    fs::write(
+        proton.join("toolmanifest.vdf"),
+        r#""manifest" { "commandline" "/proton %verb%" }"#,
+    )
+    .unwrap();
+    let mod_dir = d.path().join("mods/@cba");
+    let workshop_mod = steam.join("steamapps/workshop/content/107410/450814997");
+    for dir in [&mod_dir, &workshop_mod] {
+        fs::create_dir_all(dir.join("addons")).unwrap();
+        fs::write(dir.join("addons/stub.pbo"), b"data").unwrap();
+    }
+
+    let plan_with = |ctx: DetectionContext| {
+        let install = ctx.detect_best_install().unwrap();
+        Launcher::new(install)
+            .launch_mode(LaunchMode::Direct)
+            .mod_enabled(LocalMod::new(&mod_dir).unwrap())
+            .mod_enabled(LocalMod::new(&workshop_mod).unwrap())
+            .env("API_TOKEN", "hunter2")
+            .plan()
+    };
+
+    let plan = plan_with(DetectionContext::isolated().home(&home)).unwrap();
+    assert_eq!(plan.program(), std::path::Path::new("flatpak"));
+    // User variables are inherited by `flatpak run`, not exposed on its command line.
+    assert_eq!(plan.env(), [("API_TOKEN".into(), "hunter2".into())]);
+    let args: Vec<String> = plan
+        .args()
+        .iter()
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect();
+    let app = args
+        .iter()
+        .position(|a| a == "com.valvesoftware.Steam")
+        .unwrap();
+    assert_eq!(args[0], "run");
+    assert_eq!(
+        args[1],
+        format!("--command={}", proton.join("proton").display())
+    );
+    assert!(args[..app].contains(&format!("--filesystem={}:ro", mod_dir.display())));
+    // Mods inside a Steam library are already visible in the sandbox.
+    assert_eq!(
+        args.iter()
+            .filter(|a| a.starts_with("--filesystem="))
+            .count(),
+        1
+    );
+    assert!(!args.iter().any(|a| a.contains("hunter2")));
+    assert!(args[..app].contains(&format!(
+        "--env=STEAM_COMPAT_CLIENT_INSTALL_PATH={}",
+        steam.display()
+    )));
+    assert_eq!(args[app + 1], "run");
+
+    let err = plan_with(
+        DetectionContext::isolated()
+            .home(&home)
+            .env("FLATPAK_ID", "org.example.Frontend"),
+    )
+    .unwrap_err();
+    assert!(matches!(err, Arma3Error::UnsupportedOption { .. }));
+}
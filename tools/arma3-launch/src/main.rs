@@ -1,11 +1,19 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
-use arma3_launcher::{detect_best_install, Arma3Install, LaunchMode, Launcher};
+use arma3_launcher::{
+    detect_best_install, detect_compat_tools, doctor, trace_install_detection, Arma3Install,
+    CheckStatus, DoctorReport, LaunchMode, Launcher, LocalMod,
+};
 
 /// Simple standalone launcher for Arma 3 (sanity-check binary)
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Optional Arma 3 install directory (Steam game dir).
     #[arg(short, long)]
     dir: Option<PathBuf>,
@@ -18,11 +26,71 @@ struct Args {
     #[arg(long)]
     direct: bool,
 
+    /// Launch through a `steam://run` URL (works from sandboxed environments).
+    #[arg(long, conflicts_with = "direct")]
+    steam_url: bool,
+
+    /// Start Steam if needed and wait for it before launching; report launch failures from
+    /// Steam's logs.
+    #[arg(long, conflicts_with_all = ["direct", "steam_url"])]
+    wait_for_steam: bool,
+
+    /// Compatibility tool to use for Proton direct launches (internal or display name).
+    #[arg(long)]
+    compat_tool: Option<String>,
+
+    /// List installed compatibility tools and exit.
+    #[arg(long)]
+    list_compat_tools: bool,
+
     /// Additional arguments to pass to Arma 3
     #[arg(last = true)]
     extra: Vec<String>,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Run Steam's launch command with mods added; use as Arma 3's Steam launch options:
+    /// `arma3-launch wrap --mod ~/mods/@cba -- %command%`
+    Wrap(WrapArgs),
+    /// Check Steam, the Arma 3 install, Proton and mods, and print what to fix.
+    Doctor(DoctorArgs),
+}
+
+#[derive(clap::Args)]
+struct DoctorArgs {
+    /// Arma 3 install directory (default: detected).
+    #[arg(short, long)]
+    dir: Option<PathBuf>,
+
+    /// Mod directory to check (repeatable).
+    #[arg(long = "mod")]
+    mods: Vec<PathBuf>,
+
+    /// Compatibility tool that would be used for Proton direct launches.
+    #[arg(long)]
+    compat_tool: Option<String>,
+}
+
+#[derive(clap::Args)]
+struct WrapArgs {
+    /// Mod directory to load (repeatable).
+    #[arg(long = "mod")]
+    mods: Vec<PathBuf>,
+
+    /// Arma 3 parameter file (`-par=`).
+    #[arg(long)]
+    par: Option<PathBuf>,
+
+    /// Additional argument to pass to Arma 3 (repeatable).
+    #[arg(long = "arg", allow_hyphen_values = true)]
+    game_args: Vec<String>,
+
+    /// Steam's command line (`%command%`).
+    #[arg(last = true, required = true)]
+    command: Vec<OsString>,
+}
+
 fn find_install(provided_exe: Option<PathBuf>, install_dir: Option<PathBuf>) -> Option<Arma3Install> {
     if let Some(p) = provided_exe {
         if !p.is_file() {
@@ -44,9 +112,122 @@ fn infer_game_dir_from_exe(exe: &Path) -> Option<PathBuf> {
     exe.parent().map(|p| p.to_path_buf())
 }
 
+fn run_wrapped(args: WrapArgs) -> anyhow::Result<()> {
+    // Steam passes the game path in the command line; prefer it over detection.
+    let install = Arma3Install::from_steam_command(&args.command)
+        .ok()
+        .or_else(detect_best_install)
+        .ok_or_else(|| {
+            anyhow::anyhow!("Could not locate Arma 3 install from Steam's command line.")
+        })?;
+
+    let mods = args
+        .mods
+        .into_iter()
+        .map(LocalMod::new)
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut launcher = Launcher::new(install).mods_enabled(mods).args(args.game_args);
+    if let Some(par) = args.par {
+        launcher = launcher.par_file(par);
+    }
+
+    let plan = launcher.wrap_steam_command(&args.command)?;
+    for warning in plan.warnings() {
+        eprintln!("warning: {warning}");
+    }
+
+    let mut cmd = std::process::Command::new(plan.program());
+    cmd.args(plan.args());
+    if let Some(cwd) = plan.cwd() {
+        cmd.current_dir(cwd);
+    }
+    cmd.envs(plan.env().iter().map(|(k, v)| (k, v)));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Replace this process so Steam tracks the game directly.
+        Err(cmd.exec().into())
+    }
+    #[cfg(not(unix))]
+    {
+        let status = cmd.status()?;
+        std::process::exit(status.code().unwrap_or(1));
+    }
+}
+
+fn run_doctor(args: DoctorArgs) -> anyhow::Result<()> {
+    let install = match args.dir {
+        Some(dir) => Some(Arma3Install::new(dir)?),
+        None => detect_best_install(),
+    };
+    let Some(install) = install else {
+        let report = doctor();
+        print_report(&report);
+        println!("\nPaths probed:\n{}", trace_install_detection());
+        std::process::exit(1);
+    };
+
+    let mut launcher = Launcher::new(install);
+    let mut invalid_mods = Vec::new();
+    for dir in args.mods {
+        match LocalMod::new(&dir) {
+            Ok(m) => launcher = launcher.mod_enabled(m),
+            Err(e) => invalid_mods.push(e),
+        }
+    }
+    if let Some(tool) = args.compat_tool {
+        launcher = launcher.compat_tool(tool);
+    }
+
+    let report = launcher.doctor();
+    print_report(&report);
+    for e in &invalid_mods {
+        println!("[FAIL] mod: {e}");
+        println!("       Each mod needs a non-empty addons directory.");
+    }
+    if report.status() == CheckStatus::Fail || !invalid_mods.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn print_report(report: &DoctorReport) {
+    for check in report.checks() {
+        let tag = match check.status() {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        };
+        println!("[{tag}] {}: {}", check.name(), check.detail());
+        if let Some(hint) = check.hint() {
+            println!("       {hint}");
+        }
+    }
+}
+
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
+    match args.command {
+        Some(Command::Wrap(wrap)) => return run_wrapped(wrap),
+        Some(Command::Doctor(doctor)) => return run_doctor(doctor),
+        None => {}
+    }
+
+    if args.list_compat_tools {
+        for tool in detect_compat_tools() {
+            println!(
+                "{}\t{}\t{:?}\t{}",
+                tool.name(),
+                tool.display_name(),
+                tool.source(),
+                tool.dir().display()
+            );
+        }
+        return Ok(());
+    }
+
     let install = find_install(args.exe.clone(), args.dir.clone()).ok_or_else(|| {
         anyhow::anyhow!(
             "Could not locate Arma 3 install. Provide --dir <install_dir> or --exe <path> (or set ARMA3_DIR)."
@@ -55,6 +236,8 @@ fn main() -> anyhow::Result<()> {
 
     let mode = if args.direct {
         LaunchMode::Direct
+    } else if args.steam_url {
+        LaunchMode::SteamUrl
     } else {
         #[cfg(target_os = "linux")]
         {
@@ -66,17 +249,27 @@ fn main() -> anyhow::Result<()> {
         }
     };
 
-    let launcher = Launcher::new(install).launch_mode(mode).args(args.extra);
+    let mut launcher = Launcher::new(install).launch_mode(mode).args(args.extra);
+    if let Some(tool) = args.compat_tool {
+        launcher = launcher.compat_tool(tool);
+    }
+    if args.wait_for_steam {
+        launcher = launcher.wait_for_steam(true);
+    }
 
     let plan = launcher.plan()?;
     println!(
         "Launching Arma 3 via {}: {}",
         match mode {
             LaunchMode::ThroughSteam => "steam",
+            LaunchMode::SteamUrl => "steam URL",
             LaunchMode::Direct => "direct",
         },
         plan.program().display()
     );
+    for warning in plan.warnings() {
+        eprintln!("warning: {warning}");
+    }
     let child = plan.spawn()?;
     println!("Spawned pid: {}", child.id());
 